@@ -1,16 +1,24 @@
 use alloy_primitives::{Address, FixedBytes};
 use clickhouse::query::Query;
 
-use crate::params::BindParameters;
+use crate::params::{BindParameters, ParamValues};
 
 impl BindParameters for Address {
     fn bind_query(&self, query: Query) -> Query {
         format!("{:?}", self).bind_query(query)
     }
+
+    fn param_values(&self) -> ParamValues {
+        format!("{:?}", self).param_values()
+    }
 }
 
 impl<const N: usize> BindParameters for FixedBytes<N> {
     fn bind_query(&self, query: Query) -> Query {
         format!("{:#x}", self).bind_query(query)
     }
+
+    fn param_values(&self) -> ParamValues {
+        format!("{:#x}", self).param_values()
+    }
 }
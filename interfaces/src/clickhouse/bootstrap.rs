@@ -0,0 +1,139 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use futures::future::try_join_all;
+use regex::Regex;
+
+use super::{client::ClickhouseClient, dbms::ClickhouseDBMS, errors::ClickhouseError, migrations::on_cluster, tables::ClickhouseTableKind};
+use crate::{errors::DatabaseError, Database};
+
+impl<D> ClickhouseClient<D>
+where
+    D: ClickhouseDBMS
+{
+    /// creates the databases, then the tables of the DBMS that don't exist, a
+    /// table before its [`dependant_tables`](ClickhouseDBMS::dependant_tables).
+    /// the tables of a level of [`creation_order`] are created in parallel
+    pub async fn create_all(&self) -> Result<(), DatabaseError> {
+        let tables = D::all_tables();
+        let levels = creation_order(&tables)?;
+
+        let mut databases = Vec::new();
+        for database in tables.iter().map(D::db_name) {
+            if !database.is_empty() && !databases.contains(&database) {
+                databases.push(database);
+            }
+        }
+
+        for database in databases {
+            let create_database = on_cluster(&format!("CREATE DATABASE IF NOT EXISTS {database}"), D::CLUSTER);
+            self.execute_remote(&create_database, &()).await?;
+        }
+
+        for level in levels {
+            try_join_all(
+                level
+                    .into_iter()
+                    .map(|table| table.create_if_not_exists(self))
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// drops the tables of the DBMS that exist, in the reverse order of
+    /// [`ClickhouseClient::create_all`]. the databases are kept
+    pub async fn drop_all(&self) -> Result<(), DatabaseError> {
+        let tables = D::all_tables();
+        let levels = creation_order(&tables)?;
+
+        for level in levels.into_iter().rev() {
+            try_join_all(
+                level
+                    .into_iter()
+                    .map(|table| async move { self.execute_remote(&drop_statement(table), &()).await })
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// the tables grouped in levels: the tables of a level only depend on the
+/// tables of the previous levels (a table comes before its
+/// [`dependant_tables`](ClickhouseDBMS::dependant_tables))
+pub fn creation_order<D: ClickhouseDBMS>(tables: &[D]) -> Result<Vec<Vec<&D>>, ClickhouseError> {
+    let names = tables.iter().map(D::full_name).collect::<Vec<_>>();
+    let index = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    let dependants = |table: &D| {
+        table
+            .dependant_tables()
+            .iter()
+            .filter_map(|dependant| index.get(dependant.full_name().as_str()).copied())
+            .collect::<Vec<_>>()
+    };
+
+    // the number of tables each table depends on that aren't in a level yet
+    let mut dependencies = vec![0; tables.len()];
+    for table in tables {
+        for i in dependants(table) {
+            dependencies[i] += 1;
+        }
+    }
+
+    let mut remaining = (0..tables.len()).collect::<Vec<_>>();
+    let mut levels = Vec::new();
+    while !remaining.is_empty() {
+        let (level, rest): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|&i| dependencies[i] == 0);
+        if level.is_empty() {
+            return Err(ClickhouseError::DependencyCycle { tables: rest.into_iter().map(|i| names[i].clone()).collect() })
+        }
+
+        for &i in &level {
+            for dependant in dependants(&tables[i]) {
+                dependencies[dependant] -= 1;
+            }
+        }
+
+        levels.push(level.into_iter().map(|i| &tables[i]).collect());
+        remaining = rest;
+    }
+
+    Ok(levels)
+}
+
+/// adds `IF NOT EXISTS` to `CREATE` statements that don't have it, leaving
+/// `CREATE OR REPLACE` statements as they are
+pub fn if_not_exists(statement: &str) -> String {
+    static CREATE: OnceLock<Regex> = OnceLock::new();
+
+    let create = CREATE.get_or_init(|| {
+        Regex::new(r"(?is)^(?:\s+|--[^\n]*|/\*.*?\*/)*CREATE\s+(?:TABLE|(?:MATERIALIZED\s+)?VIEW|DICTIONARY|DATABASE)(\s+IF\s+NOT\s+EXISTS)?\b")
+            .unwrap()
+    });
+
+    match create.captures(statement) {
+        Some(captures) if captures.get(1).is_none() => {
+            let end = captures.get(0).unwrap().end();
+            format!("{} IF NOT EXISTS{}", &statement[..end], &statement[end..])
+        }
+        _ => statement.to_string()
+    }
+}
+
+/// the `DROP ... IF EXISTS` statement of the table, `ON CLUSTER` when the DBMS
+/// has a cluster
+pub fn drop_statement<D: ClickhouseDBMS>(table: &D) -> String {
+    let object = match table.table_type() {
+        ClickhouseTableKind::Dictionary => "DICTIONARY",
+        ClickhouseTableKind::View | ClickhouseTableKind::MaterializedView => "VIEW",
+        _ => "TABLE"
+    };
+
+    on_cluster(&format!("DROP {object} IF EXISTS {} SYNC", table.full_name()), D::CLUSTER)
+}
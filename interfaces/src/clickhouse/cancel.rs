@@ -0,0 +1,71 @@
+use clickhouse::Client;
+
+use crate::errors::DatabaseError;
+
+/// random id given to the queries that don't have one set in their
+/// [`QueryOptions`](crate::options::QueryOptions)
+pub(crate) fn random_query_id() -> String {
+    let id = rand::random::<u128>();
+    format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}", id >> 96, (id >> 80) & 0xffff, (id >> 64) & 0xffff, (id >> 48) & 0xffff, id & 0xffff_ffff_ffff)
+}
+
+/// the `query_id` of an attempt of a request: the one of the
+/// [`QueryOptions`](crate::options::QueryOptions) for the first attempt and
+/// `<query_id>-<attempt>` for the retries and failovers, so a retry isn't
+/// rejected or killed because the previous attempt is still being killed.
+/// random without one
+pub fn attempt_query_id(query_id: Option<&str>, attempt: u32) -> String {
+    match query_id {
+        Some(query_id) if attempt == 0 => query_id.to_string(),
+        Some(query_id) => format!("{query_id}-{attempt}"),
+        None => random_query_id()
+    }
+}
+
+/// `KILL QUERY` of the query, on every node of the cluster if there is one
+pub(crate) async fn kill_query(client: &Client, cluster: Option<&str>, query_id: &str) -> Result<(), DatabaseError> {
+    let on_cluster = cluster
+        .map(|cluster| format!("ON CLUSTER {cluster} "))
+        .unwrap_or_default();
+
+    Ok(client
+        .query(&format!("KILL QUERY {on_cluster}WHERE query_id = ? ASYNC"))
+        .bind(query_id)
+        .execute()
+        .await?)
+}
+
+/// kills the query on the server when dropped before being disarmed, i.e. when
+/// the future or stream running the query is dropped before it completes
+pub(crate) struct KillOnDrop {
+    client:   Client,
+    cluster:  Option<&'static str>,
+    query_id: Option<String>
+}
+
+impl KillOnDrop {
+    pub(crate) fn new(client: Client, cluster: Option<&'static str>, query_id: String) -> Self {
+        Self { client, cluster, query_id: Some(query_id) }
+    }
+
+    /// the query completed, there's nothing to kill
+    pub(crate) fn disarm(mut self) {
+        self.query_id = None;
+    }
+}
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let Some(query_id) = self.query_id.take() else { return };
+
+        // without a runtime (e.g. dropped while the runtime shuts down) the query
+        // can't be killed, it runs until the server's `max_execution_time`
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else { return };
+
+        let client = self.client.clone();
+        let cluster = self.cluster;
+        runtime.spawn(async move {
+            let _ = kill_query(&client, cluster, &query_id).await;
+        });
+    }
+}
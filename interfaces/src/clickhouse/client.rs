@@ -1,15 +1,48 @@
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc
+    },
+    time::Duration
+};
 
 use clickhouse::{query::Query, *};
 use eyre::Result;
+use futures::{future::join_all, Future, Stream, StreamExt, TryStreamExt};
 
-use super::{dbms::ClickhouseDBMS, types::ClickhouseQuery};
-use crate::{errors::DatabaseError, params::BindParameters, Database, DatabaseTable};
+use super::{
+    cancel::{attempt_query_id, kill_query, random_query_id, KillOnDrop},
+    dbms::ClickhouseDBMS,
+    endpoints::{Endpoint, Endpoints},
+    errors::ClickhouseError,
+    instrument::{Operation, OperationKind},
+    retry::RetryPolicy,
+    types::ClickhouseBackend
+};
+use crate::{
+    errors::DatabaseError, inserts::estimated_size, options::QueryOptions, params::BindParameters, Database, DatabaseInsert, DatabaseQuery,
+    DatabaseTable
+};
 
 #[derive(Clone)]
 pub struct ClickhouseClient<D> {
-    pub client:   Client,
-    pub _phantom: PhantomData<D>
+    /// the client of the primary endpoint
+    pub client:           Client,
+    /// every endpoint (the primary one included) requests are spread over and
+    /// fail over to
+    pub endpoints:        Arc<Endpoints>,
+    /// retries of failed inserts, queries and executes
+    pub retry:            RetryPolicy,
+    /// time after which an attempt of a request fails with
+    /// [`error::Error::TimedOut`]
+    pub request_timeout:  Option<Duration>,
+    /// directory of the `CREATE` statements replacing the ones embedded in the
+    /// tables, see
+    /// [`ClickhouseTable::create_sql`](super::tables::ClickhouseTable::create_sql)
+    pub sql_override_dir: Option<PathBuf>,
+    pub _phantom:         PhantomData<D>
 }
 
 impl<D> ClickhouseClient<D>
@@ -29,77 +62,348 @@ where
     }
 }
 
-//#[async_trait::async_trait]
-impl<D> Database for ClickhouseClient<D>
+impl<D> ClickhouseClient<D>
 where
     D: ClickhouseDBMS
 {
-    type DBMS = D;
+    /// the client sending the settings, `query_id` and `log_comment` of the
+    /// options with its requests
+    pub fn client_with_options(&self, options: &QueryOptions) -> Client {
+        with_options(&self.client, options)
+    }
 
-    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError> {
-        let mut insert = self
-            .client
-            .insert(Self::DBMS::from_database_table_str(T::NAME).full_name())?;
+    /// kills the query on the server, on every node if the DBMS has a cluster
+    /// and on every endpoint otherwise
+    pub async fn kill_query(&self, query_id: &str) -> Result<(), DatabaseError> {
+        let results = join_all(
+            self.endpoints
+                .iter()
+                .map(|endpoint| kill_query(&endpoint.client, D::CLUSTER, query_id))
+        )
+        .await;
 
-        insert.write(value).await?;
+        if results.iter().any(Result::is_ok) {
+            return Ok(())
+        }
+        results.into_iter().collect()
+    }
 
-        insert.end().await?;
+    /// runs the request with the options and the retry policy, `f` is given
+    /// the client of each attempt
+    ///
+    /// every attempt is sent to the endpoints picked by the load balancing,
+    /// failing over to the next one on errors the retry policy would retry.
+    /// each request to an endpoint has its own `query_id`, see
+    /// [`attempt_query_id`] (the `log_comment` is the `query_id` of the options
+    /// when it isn't set), and one that doesn't complete (it timed out, the
+    /// options' cancellation token was cancelled or the returned future was
+    /// dropped) is killed on the server
+    pub async fn run_query<T, F, Fut>(&self, options: &QueryOptions, f: F) -> Result<T, DatabaseError>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T, DatabaseError>>
+    {
+        let f = &f;
+        let timeout = options.timeout.or(self.request_timeout);
+        let attempts = &AtomicU32::new(0);
+        let request = self.retry.run(|| async move {
+            let mut error = None;
+            for endpoint in self.endpoints.candidates() {
+                let attempt = attempts.fetch_add(1, Ordering::Relaxed);
+                match self
+                    .run_on_endpoint(endpoint, options, attempt, timeout, f)
+                    .await
+                {
+                    Ok(res) => {
+                        endpoint.record_success();
+                        return Ok(res)
+                    }
+                    Err(e) if (self.retry.classifier)(&e) => {
+                        endpoint.record_failure(&self.endpoints.load_balancing);
+                        error = Some(e);
+                    }
+                    Err(e) => return Err(e)
+                }
+            }
 
-        Ok(())
+            Err(error.expect("there is at least one endpoint"))
+        });
+
+        match &options.cancellation {
+            Some(cancellation) => tokio::select! {
+                res = request => res,
+                _ = cancellation.cancelled() => Err(ClickhouseError::Cancelled.into())
+            },
+            None => request.await
+        }
     }
 
-    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError> {
-        let mut insert = self
-            .client
-            .insert(Self::DBMS::from_database_table_str(T::NAME).full_name())?;
+    /// a single request to the endpoint, killed on the server if it doesn't
+    /// complete
+    async fn run_on_endpoint<T, F, Fut>(
+        &self,
+        endpoint: &Endpoint,
+        options: &QueryOptions,
+        attempt: u32,
+        timeout: Option<Duration>,
+        f: &F
+    ) -> Result<T, DatabaseError>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T, DatabaseError>>
+    {
+        let query_id = attempt_query_id(options.query_id.as_deref(), attempt);
+        let kill_on_drop = KillOnDrop::new(endpoint.client.clone(), D::CLUSTER, query_id.clone());
 
-        for value in values {
-            insert.write(value).await?;
+        let mut client = with_options(&endpoint.client, options).with_option("query_id", query_id);
+        if let (Some(query_id), None) = (&options.query_id, &options.log_comment) {
+            client = client.with_option("log_comment", query_id);
         }
+        let attempt = f(client);
+
+        let res = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, attempt)
+                .await
+                .map_err(|_| error::Error::TimedOut)?,
+            None => attempt.await
+        };
+        kill_on_drop.disarm();
+
+        res
+    }
+
+    /// inserts the rows into the table, retried inserts send the same
+    /// `insert_deduplication_token` so a retried insert whose first attempt was
+    /// written isn't inserted twice
+    pub async fn insert_rows<R: DatabaseInsert<ClickhouseBackend>>(&self, table: &str, rows: &[R]) -> Result<(), DatabaseError> {
+        self.insert_rows_with_options(table, rows, &QueryOptions::default())
+            .await
+    }
+
+    pub async fn insert_rows_with_options<R: DatabaseInsert<ClickhouseBackend>>(
+        &self,
+        table: &str,
+        rows: &[R],
+        options: &QueryOptions
+    ) -> Result<(), DatabaseError> {
+        self.insert_table_rows(options.table, table, rows, options)
+            .await
+    }
 
-        insert.end().await?;
+    /// inserts the rows into the table, measured as an insert into the table
+    /// named `name`
+    pub(crate) async fn insert_table_rows<R: DatabaseInsert<ClickhouseBackend>>(
+        &self,
+        name: Option<&'static str>,
+        table: &str,
+        rows: &[R],
+        options: &QueryOptions
+    ) -> Result<(), DatabaseError> {
+        let token = (self.retry.max_attempts > 1 && self.retry.deduplicate_inserts).then(|| format!("{:032x}", rand::random::<u128>()));
+
+        let request = self.run_query(options, |client| {
+            let client = match &token {
+                Some(token) => client.with_option("insert_deduplication_token", token),
+                None => client
+            };
+
+            async move { R::insert_rows(&client, table, rows).await }
+        });
 
-        Ok(())
+        Operation::insert(table, name)
+            .measure(request, |_| (rows.len(), rows.iter().map(estimated_size).sum()))
+            .await
     }
+}
+
+//#[async_trait::async_trait]
+impl<D> Database for ClickhouseClient<D>
+where
+    D: ClickhouseDBMS
+{
+    type Backend = ClickhouseBackend;
+    type DBMS = D;
 
-    async fn query_one<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<Q, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        self.insert_many::<T>(std::slice::from_ref(value)).await
+    }
 
-        let res = query.fetch_one::<Q>().await?;
+    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        self.insert_many_with_options::<T>(values, &QueryOptions::default())
+            .await
+    }
 
-        Ok(res)
+    async fn query_one<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Q, DatabaseError> {
+        self.query_one_with_options(query, params, &QueryOptions::default())
+            .await
     }
 
-    async fn query_one_optional<Q: ClickhouseQuery, P: BindParameters>(
+    async fn query_one_optional<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
         &self,
         query: impl AsRef<str> + Send,
         params: &P
     ) -> Result<Option<Q>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+        self.query_one_optional_with_options(query, params, &QueryOptions::default())
+            .await
+    }
+
+    async fn query_many<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<Q>, DatabaseError> {
+        self.query_many_with_options(query, params, &QueryOptions::default())
+            .await
+    }
 
-        let res = query.fetch_optional::<Q>().await?;
+    /// not retried nor failed over, a failure midway through would repeat the
+    /// rows already streamed
+    fn query_stream<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> impl Stream<Item = Result<Q, DatabaseError>> + Send {
+        let query = query.as_ref();
+        let endpoint = self.endpoints.candidates()[0];
+        let query_id = random_query_id();
+        let kill_on_drop = KillOnDrop::new(endpoint.client.clone(), D::CLUSTER, query_id.clone());
+        let client = endpoint.client.clone().with_option("query_id", query_id);
+        let operation = Operation::query(OperationKind::QueryStream, query, None);
+
+        // the query is killed if the stream is dropped before its last row, and
+        // only measured if it isn't
+        futures::stream::unfold(
+            (Q::fetch_rows(params.bind_query(client.query(query))), Some(kill_on_drop), Some(operation), 0),
+            |(mut rows, mut kill_on_drop, mut operation, count)| async move {
+                match rows.next().await {
+                    Some(Ok(row)) => Some((Ok(row), (rows, kill_on_drop, operation, count + 1))),
+                    Some(Err(e)) => {
+                        if let Some(operation) = operation.take() {
+                            operation.finish(Some(&e), count, 0)
+                        }
+                        Some((Err(e), (rows, kill_on_drop, operation, count)))
+                    }
+                    None => {
+                        kill_on_drop.take().map(KillOnDrop::disarm);
+                        if let Some(operation) = operation.take() {
+                            operation.finish(None, count, 0)
+                        }
+                        None
+                    }
+                }
+            }
+        )
+    }
 
-        Ok(res)
+    async fn query_raw<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<u8>, DatabaseError> {
+        self.query_raw_with_options::<Q, P>(query, params, &QueryOptions::default())
+            .await
     }
 
-    async fn query_many<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<Vec<Q>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    async fn execute_remote<P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<(), DatabaseError> {
+        self.execute_remote_with_options(query, params, &QueryOptions::default())
+            .await
+    }
 
-        let res = query.fetch_all::<Q>().await?;
+    async fn insert_many_with_options<T: DatabaseTable>(&self, values: &[T::DataType], options: &QueryOptions) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        let table = Self::DBMS::from_database_table_str(T::NAME).full_name();
 
-        Ok(res)
+        self.insert_table_rows(Some(T::NAME), &table, values, options)
+            .await
     }
 
-    async fn query_raw<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<Vec<u8>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
-        Ok(query.fetch_raw::<Q>().await?)
+    async fn query_one_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Q, DatabaseError> {
+        self.query_one_optional_with_options(query, params, options)
+            .await?
+            .ok_or_else(|| clickhouse::error::Error::RowNotFound.into())
     }
 
-    async fn execute_remote<P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<(), DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    async fn query_one_optional_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Option<Q>, DatabaseError> {
+        let query = query.as_ref();
+        let request = self.run_query(options, |client| async move {
+            Q::fetch_rows(params.bind_query(client.query(query)))
+                .try_next()
+                .await
+        });
+
+        Operation::query(OperationKind::Query, query, options.table)
+            .measure(request, |row| (row.is_some() as usize, 0))
+            .await
+    }
+
+    async fn query_many_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Vec<Q>, DatabaseError> {
+        let query = query.as_ref();
+        let request = self.run_query(options, |client| Q::fetch_rows(params.bind_query(client.query(query))).try_collect());
+
+        Operation::query(OperationKind::Query, query, options.table)
+            .measure(request, |rows: &Vec<Q>| (rows.len(), 0))
+            .await
+    }
 
-        query.execute().await?;
+    async fn query_raw_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Vec<u8>, DatabaseError> {
+        let query = query.as_ref();
+        let request = self.run_query(options, |client| Q::fetch_raw(params.bind_query(client.query(query))));
 
-        Ok(())
+        Operation::query(OperationKind::QueryRaw, query, options.table)
+            .measure(request, |raw| (0, raw.len()))
+            .await
     }
+
+    async fn execute_remote_with_options<P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<(), DatabaseError> {
+        let query = query.as_ref();
+        let request = self.run_query(options, |client| async move { Ok(params.bind_query(client.query(query)).execute().await?) });
+
+        Operation::query(OperationKind::Execute, query, options.table)
+            .measure(request, |_| (0, 0))
+            .await
+    }
+}
+
+/// the client with the settings, `query_id` and `log_comment` of the options
+fn with_options(client: &Client, options: &QueryOptions) -> Client {
+    options
+        .server_params()
+        .fold(client.clone(), |client, (name, value)| client.with_option(name, value))
 }
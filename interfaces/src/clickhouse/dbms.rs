@@ -1,6 +1,6 @@
 use std::pin::Pin;
 
-use super::client::ClickhouseClient;
+use super::{client::ClickhouseClient, schema::SchemaDiff, tables::ClickhouseTableKind};
 use crate::errors::DatabaseError;
 
 pub trait ClickhouseDBMS: Sized + Sync + Send {
@@ -13,8 +13,23 @@ pub trait ClickhouseDBMS: Sized + Sync + Send {
         database: &'a ClickhouseClient<Self>
     ) -> Pin<Box<dyn std::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
 
+    /// creates the table if it doesn't exist, without its dependant tables
+    fn create_if_not_exists<'a>(
+        &'a self,
+        database: &'a ClickhouseClient<Self>
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
+
+    /// compares the columns of the table to the live table, see
+    /// [`ClickhouseTable::check_schema`](super::tables::ClickhouseTable::check_schema)
+    fn check_schema<'a>(
+        &'a self,
+        database: &'a ClickhouseClient<Self>
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<SchemaDiff, DatabaseError>> + Send + 'a>>;
+
     fn all_tables() -> Vec<Self>;
 
+    fn table_type(&self) -> ClickhouseTableKind;
+
     /// <DB NAME>.<TABLE NAME>
     fn full_name(&self) -> String;
 
@@ -87,6 +102,38 @@ macro_rules! clickhouse_dbms {
 
             }
 
+            fn create_if_not_exists<'a>(&'a self, database: &'a ::db_interfaces::clickhouse::client::ClickhouseClient<Self>)
+                 -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), ::db_interfaces::errors::DatabaseError>> + Send + 'a>> {
+                Box::pin(async move {
+                    match self {
+                        $($dbms::$table => {
+                            <$table as ::db_interfaces::clickhouse::tables::ClickhouseTable<Self>>::create_if_not_exists(database).await
+                        })*
+                    }
+                })
+            }
+
+            fn check_schema<'a>(&'a self, database: &'a ::db_interfaces::clickhouse::client::ClickhouseClient<Self>)
+                 -> std::pin::Pin<Box<dyn std::future::Future<
+                    Output = Result<::db_interfaces::clickhouse::schema::SchemaDiff, ::db_interfaces::errors::DatabaseError>
+                > + Send + 'a>> {
+                Box::pin(async move {
+                    match self {
+                        $($dbms::$table => {
+                            <$table as ::db_interfaces::clickhouse::tables::ClickhouseTable<Self>>::check_schema(database).await
+                        })*
+                    }
+                })
+            }
+
+            fn table_type(&self) -> ::db_interfaces::clickhouse::tables::ClickhouseTableKind {
+                match self {
+                    $($dbms::$table => {
+                        <$table as ::db_interfaces::clickhouse::tables::ClickhouseTable<Self>>::TABLE_TYPE
+                    })*
+                }
+            }
+
             fn db_name(&self) -> String {
                 match self {
                     $($dbms::$table => {
@@ -184,6 +231,38 @@ macro_rules! clickhouse_dbms {
 
             }
 
+            fn create_if_not_exists<'a>(&'a self, database: &'a ::db_interfaces::clickhouse::client::ClickhouseClient<Self>)
+                 -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), ::db_interfaces::errors::DatabaseError>> + Send + 'a>> {
+                Box::pin(async move {
+                    match self {
+                        $($dbms::$table => {
+                            <$table as ::db_interfaces::clickhouse::tables::ClickhouseTable<Self>>::create_if_not_exists(database).await
+                        })*
+                    }
+                })
+            }
+
+            fn check_schema<'a>(&'a self, database: &'a ::db_interfaces::clickhouse::client::ClickhouseClient<Self>)
+                 -> std::pin::Pin<Box<dyn std::future::Future<
+                    Output = Result<::db_interfaces::clickhouse::schema::SchemaDiff, ::db_interfaces::errors::DatabaseError>
+                > + Send + 'a>> {
+                Box::pin(async move {
+                    match self {
+                        $($dbms::$table => {
+                            <$table as ::db_interfaces::clickhouse::tables::ClickhouseTable<Self>>::check_schema(database).await
+                        })*
+                    }
+                })
+            }
+
+            fn table_type(&self) -> ::db_interfaces::clickhouse::tables::ClickhouseTableKind {
+                match self {
+                    $($dbms::$table => {
+                        <$table as ::db_interfaces::clickhouse::tables::ClickhouseTable<Self>>::TABLE_TYPE
+                    })*
+                }
+            }
+
             fn db_name(&self) -> String {
                 match self {
                     $($dbms::$table => {
@@ -265,10 +344,28 @@ impl ClickhouseDBMS for NullDBMS {
         Box::pin(async { Ok(()) })
     }
 
+    fn create_if_not_exists(
+        &self,
+        _database: &ClickhouseClient<Self>
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<(), DatabaseError>> + Send>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn check_schema(
+        &self,
+        _database: &ClickhouseClient<Self>
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<SchemaDiff, DatabaseError>> + Send>> {
+        Box::pin(async { Ok(SchemaDiff::default()) })
+    }
+
     fn all_tables() -> Vec<Self> {
         Vec::new()
     }
 
+    fn table_type(&self) -> ClickhouseTableKind {
+        ClickhouseTableKind::None
+    }
+
     /// <DB NAME>.<TABLE NAME>
     fn full_name(&self) -> String {
         String::new()
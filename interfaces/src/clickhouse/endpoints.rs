@@ -0,0 +1,155 @@
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        Mutex
+    },
+    time::{Duration, Instant}
+};
+
+use clickhouse::Client;
+use serde::Deserialize;
+
+use super::utils::deserialize_millis;
+
+/// how the endpoint of a request is picked among the replicas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadBalancingStrategy {
+    /// each request starts at the endpoint after the one of the previous
+    /// request
+    #[default]
+    RoundRobin,
+    /// each request starts at a random endpoint
+    Random,
+    /// every request starts at the first endpoint, the others are only used
+    /// when it fails or is ejected
+    FirstHealthy
+}
+
+impl FromStr for LoadBalancingStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round_robin" => Ok(LoadBalancingStrategy::RoundRobin),
+            "random" => Ok(LoadBalancingStrategy::Random),
+            "first_healthy" => Ok(LoadBalancingStrategy::FirstHealthy),
+            _ => Err(format!("`{s}` is not one of round_robin, random or first_healthy"))
+        }
+    }
+}
+
+/// how requests are spread over the endpoints and when a failing endpoint is
+/// ejected
+///
+/// when deserialized, missing fields are taken from the default config and the
+/// ejection is in milliseconds (`ejection_ms`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoadBalancing {
+    pub strategy:          LoadBalancingStrategy,
+    /// consecutive failed requests after which an endpoint is ejected
+    pub failure_threshold: u32,
+    /// an ejected endpoint isn't used for this long, the next request sent to
+    /// it afterwards probes it: it's ejected again if it fails and back in
+    /// rotation if it succeeds
+    #[serde(rename = "ejection_ms", deserialize_with = "deserialize_millis")]
+    pub ejection:          Duration
+}
+
+impl Default for LoadBalancing {
+    fn default() -> Self {
+        Self { strategy: LoadBalancingStrategy::default(), failure_threshold: 3, ejection: Duration::from_secs(30) }
+    }
+}
+
+/// a replica the requests can be sent to, with its passive health
+pub struct Endpoint {
+    pub url:              String,
+    pub client:           Client,
+    consecutive_failures: AtomicU32,
+    ejected_until:        Mutex<Option<Instant>>
+}
+
+impl Endpoint {
+    pub fn new(url: String, client: Client) -> Self {
+        Self { url, client, consecutive_failures: AtomicU32::new(0), ejected_until: Mutex::new(None) }
+    }
+
+    /// false while the endpoint is ejected
+    pub fn is_available(&self) -> bool {
+        self.ejected_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map_or(true, |until| until <= Instant::now())
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures.load(Ordering::Relaxed)
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        *self.ejected_until.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    pub fn record_failure(&self, load_balancing: &LoadBalancing) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= load_balancing.failure_threshold {
+            *self.ejected_until.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + load_balancing.ejection);
+        }
+    }
+}
+
+/// the endpoints of a [`ClickhouseClient`](super::client::ClickhouseClient),
+/// never empty
+pub struct Endpoints {
+    endpoints:          Vec<Endpoint>,
+    pub load_balancing: LoadBalancing,
+    next:               AtomicUsize
+}
+
+impl Endpoints {
+    /// panics if there are no endpoints
+    pub fn new(endpoints: Vec<Endpoint>, load_balancing: LoadBalancing) -> Self {
+        assert!(!endpoints.is_empty(), "a clickhouse client needs at least one endpoint");
+
+        Self { endpoints, load_balancing, next: AtomicUsize::new(0) }
+    }
+
+    /// the first endpoint, the `url` of the config
+    pub fn primary(&self) -> &Endpoint {
+        &self.endpoints[0]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Endpoint> {
+        self.endpoints.iter()
+    }
+
+    /// the endpoints a request is sent to, in order: the first one is tried and
+    /// the request fails over to the next ones. ejected endpoints are skipped,
+    /// unless they all are
+    pub fn candidates(&self) -> Vec<&Endpoint> {
+        let start = match self.load_balancing.strategy {
+            LoadBalancingStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed),
+            LoadBalancingStrategy::Random => rand::random(),
+            LoadBalancingStrategy::FirstHealthy => 0
+        } % self.endpoints.len();
+
+        let ordered = self.endpoints[start..]
+            .iter()
+            .chain(&self.endpoints[..start]);
+
+        let available = ordered
+            .clone()
+            .filter(|endpoint| endpoint.is_available())
+            .collect::<Vec<_>>();
+
+        if available.is_empty() {
+            ordered.collect()
+        } else {
+            available
+        }
+    }
+}
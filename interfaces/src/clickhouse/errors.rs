@@ -7,7 +7,160 @@ pub enum ClickhouseError {
     #[error("clickhouse error: {0}")]
     ClickhouseNative(clickhouse::error::Error),
     #[error("error reading clickhouse sql file: {0}")]
-    SqlFileReadError(String)
+    SqlFileReadError(String),
+    #[error("error sending to the buffered clickhouse client: {0}")]
+    SharedSendError(String),
+    /// the [`CancellationToken`](crate::options::CancellationToken) of the
+    /// call was cancelled
+    #[error("clickhouse query cancelled")]
+    Cancelled,
+    #[error("invalid migration file `{file}`: {reason}")]
+    InvalidMigration { file: String, reason: String },
+    /// the file of a migration that was already applied changed
+    #[error("migration {version} `{name}` changed since it was applied")]
+    MigrationChanged { version: u64, name: String },
+    /// the file of a migration that was already applied isn't in the directory
+    #[error("migration {version} `{name}` was applied but its file is missing")]
+    MigrationMissing { version: u64, name: String },
+    /// the [`dependant_tables`](super::dbms::ClickhouseDBMS::dependant_tables)
+    /// of the tables, or of the tables they depend on, form a cycle
+    #[error("cyclic table dependencies between {}", tables.join(", "))]
+    DependencyCycle { tables: Vec<String> },
+    /// the table (e.g. a `Null` table) doesn't store rows to delete
+    #[error("table {0} stores no rows")]
+    NoStorageTable(String),
+    /// a part of a [`SelectQuery`](super::query_builder::SelectQuery) is
+    /// invalid, e.g. a sample ratio that isn't in `(0, 1]`
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+    #[error("invalid clickhouse config `{field}`: {reason}")]
+    InvalidConfig { field: String, reason: String },
+    #[error("unknown table (code {code}): {message}")]
+    UnknownTable { code: u32, message: String },
+    #[error("unknown database (code {code}): {message}")]
+    UnknownDatabase { code: u32, message: String },
+    #[error("syntax error (code {code}): {message}")]
+    SyntaxError { code: u32, message: String },
+    /// `TIMEOUT_EXCEEDED` or `SOCKET_TIMEOUT`
+    #[error("timeout (code {code}): {message}")]
+    Timeout { code: u32, message: String },
+    #[error("memory limit exceeded (code {code}): {message}")]
+    MemoryLimitExceeded { code: u32, message: String },
+    #[error("too many parts (code {code}): {message}")]
+    TooManyParts { code: u32, message: String },
+    /// `READONLY` (the user is readonly) or `TABLE_IS_READ_ONLY` (the replica
+    /// lost its connection to keeper)
+    #[error("readonly mode (code {code}): {message}")]
+    ReadonlyMode { code: u32, message: String },
+    /// any other exception returned by the server
+    #[error("clickhouse server exception (code {code}): {message}")]
+    ServerException { code: u32, message: String }
+}
+
+/// codes of the exceptions returned by the server, from `ErrorCodes.cpp`
+pub mod codes {
+    pub const UNKNOWN_TABLE: u32 = 60;
+    pub const SYNTAX_ERROR: u32 = 62;
+    pub const UNKNOWN_DATABASE: u32 = 81;
+    pub const TIMEOUT_EXCEEDED: u32 = 159;
+    pub const READONLY: u32 = 164;
+    pub const TOO_MANY_SIMULTANEOUS_QUERIES: u32 = 202;
+    pub const SOCKET_TIMEOUT: u32 = 209;
+    pub const NETWORK_ERROR: u32 = 210;
+    pub const NO_REPLICA_HAS_PART: u32 = 234;
+    pub const MEMORY_LIMIT_EXCEEDED: u32 = 241;
+    pub const TABLE_IS_READ_ONLY: u32 = 242;
+    pub const TOO_MANY_PARTS: u32 = 252;
+    pub const ALL_CONNECTION_TRIES_FAILED: u32 = 279;
+    pub const REPLICA_IS_NOT_IN_QUORUM: u32 = 289;
+    pub const ALL_REPLICAS_ARE_STALE: u32 = 369;
+    pub const KEEPER_EXCEPTION: u32 = 999;
+}
+
+impl ClickhouseError {
+    pub fn invalid_config(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        ClickhouseError::InvalidConfig { field: field.into(), reason: reason.into() }
+    }
+
+    /// parses a `Code: NNN. DB::Exception: ...` response from the server (or
+    /// `Code: NNN, e.displayText() = DB::Exception: ...` from older versions)
+    pub fn from_server_response(response: &str) -> Option<Self> {
+        let (_, rest) = response.split_once("Code: ")?;
+        let code_len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let code = rest[..code_len].parse().ok()?;
+
+        let message = match rest.split_once("DB::Exception:") {
+            Some((_, message)) => message,
+            None => rest[code_len..].trim_start_matches(['.', ','])
+        };
+
+        Some(Self::from_code(code, message.trim().to_string()))
+    }
+
+    /// the variant for the code of a server exception
+    pub fn from_code(code: u32, message: String) -> Self {
+        match code {
+            codes::UNKNOWN_TABLE => ClickhouseError::UnknownTable { code, message },
+            codes::UNKNOWN_DATABASE => ClickhouseError::UnknownDatabase { code, message },
+            codes::SYNTAX_ERROR => ClickhouseError::SyntaxError { code, message },
+            codes::TIMEOUT_EXCEEDED | codes::SOCKET_TIMEOUT => ClickhouseError::Timeout { code, message },
+            codes::MEMORY_LIMIT_EXCEEDED => ClickhouseError::MemoryLimitExceeded { code, message },
+            codes::TOO_MANY_PARTS => ClickhouseError::TooManyParts { code, message },
+            codes::READONLY | codes::TABLE_IS_READ_ONLY => ClickhouseError::ReadonlyMode { code, message },
+            _ => ClickhouseError::ServerException { code, message }
+        }
+    }
+
+    /// name of the variant, e.g. `too_many_parts`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClickhouseError::ClickhouseNative(_) => "native",
+            ClickhouseError::SqlFileReadError(_) => "sql_file_read",
+            ClickhouseError::SharedSendError(_) => "shared_send",
+            ClickhouseError::Cancelled => "cancelled",
+            ClickhouseError::DependencyCycle { .. } => "dependency_cycle",
+            ClickhouseError::NoStorageTable(_) => "no_storage_table",
+            ClickhouseError::InvalidQuery(_) => "invalid_query",
+            ClickhouseError::InvalidConfig { .. } => "invalid_config",
+            ClickhouseError::InvalidMigration { .. } => "invalid_migration",
+            ClickhouseError::MigrationChanged { .. } => "migration_changed",
+            ClickhouseError::MigrationMissing { .. } => "migration_missing",
+            ClickhouseError::UnknownTable { .. } => "unknown_table",
+            ClickhouseError::UnknownDatabase { .. } => "unknown_database",
+            ClickhouseError::SyntaxError { .. } => "syntax_error",
+            ClickhouseError::Timeout { .. } => "timeout",
+            ClickhouseError::MemoryLimitExceeded { .. } => "memory_limit_exceeded",
+            ClickhouseError::TooManyParts { .. } => "too_many_parts",
+            ClickhouseError::ReadonlyMode { .. } => "readonly_mode",
+            ClickhouseError::ServerException { .. } => "server_exception"
+        }
+    }
+
+    /// code of the server exception
+    pub fn code(&self) -> Option<u32> {
+        self.server_exception().map(|(code, _)| code)
+    }
+
+    /// message of the server exception
+    pub fn message(&self) -> Option<&str> {
+        self.server_exception().map(|(_, message)| message)
+    }
+
+    fn server_exception(&self) -> Option<(u32, &str)> {
+        match self {
+            ClickhouseError::UnknownTable { code, message }
+            | ClickhouseError::UnknownDatabase { code, message }
+            | ClickhouseError::SyntaxError { code, message }
+            | ClickhouseError::Timeout { code, message }
+            | ClickhouseError::MemoryLimitExceeded { code, message }
+            | ClickhouseError::TooManyParts { code, message }
+            | ClickhouseError::ReadonlyMode { code, message }
+            | ClickhouseError::ServerException { code, message } => Some((*code, message)),
+            _ => None
+        }
+    }
 }
 
 impl From<std::io::Error> for ClickhouseError {
@@ -18,6 +171,12 @@ impl From<std::io::Error> for ClickhouseError {
 
 impl From<clickhouse::error::Error> for ClickhouseError {
     fn from(value: clickhouse::error::Error) -> ClickhouseError {
+        if let clickhouse::error::Error::BadResponse(response) = &value {
+            if let Some(exception) = ClickhouseError::from_server_response(response) {
+                return exception
+            }
+        }
+
         ClickhouseError::ClickhouseNative(value)
     }
 }
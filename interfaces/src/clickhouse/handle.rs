@@ -0,0 +1,130 @@
+use std::marker::PhantomData;
+
+use clickhouse::DbRow;
+
+use super::{client::ClickhouseClient, dbms::ClickhouseDBMS, errors::ClickhouseError, migrations::on_cluster, tables::ClickhouseTable};
+use crate::{errors::DatabaseError, options::QueryOptions, params::BindParameters, tables::DatabaseTable, Database, DatabaseInsert, DatabaseQuery};
+
+/// the rows of a single table, typed to its
+/// [`ClickhouseDataType`](ClickhouseTable::ClickhouseDataType). created with
+/// [`ClickhouseClient::table`], works on any [`Database`] (e.g. the
+/// `ClickhouseTestClient` or the `MockDatabase`)
+pub struct TableHandle<'a, T, D, DB = ClickhouseClient<D>> {
+    database: &'a DB,
+    _phantom: PhantomData<fn() -> (T, D)>
+}
+
+impl<'a, T, D, DB> TableHandle<'a, T, D, DB>
+where
+    T: ClickhouseTable<D> + DatabaseTable<DataType = <T as ClickhouseTable<D>>::ClickhouseDataType>,
+    D: ClickhouseDBMS + Send + Sync + 'static,
+    DB: Database
+{
+    pub fn new(database: &'a DB) -> Self {
+        Self { database, _phantom: PhantomData }
+    }
+
+    pub async fn insert(&self, row: &T::ClickhouseDataType) -> Result<(), DatabaseError>
+    where
+        T::ClickhouseDataType: DatabaseInsert<DB::Backend>
+    {
+        self.database.insert_one::<T>(row).await
+    }
+
+    pub async fn insert_many(&self, rows: &[T::ClickhouseDataType]) -> Result<(), DatabaseError>
+    where
+        T::ClickhouseDataType: DatabaseInsert<DB::Backend>
+    {
+        self.database.insert_many::<T>(rows).await
+    }
+
+    /// the number of rows of the table
+    pub async fn count(&self) -> Result<u64, DatabaseError>
+    where
+        u64: DatabaseQuery<DB::Backend>
+    {
+        self.database
+            .query_one_with_options::<u64, _>(format!("SELECT count() FROM {}", T::full_name()), &(), &Self::options())
+            .await
+    }
+
+    pub async fn select_all(&self) -> Result<Vec<T::ClickhouseDataType>, DatabaseError>
+    where
+        T::ClickhouseDataType: DbRow + DatabaseQuery<DB::Backend>
+    {
+        self.database
+            .query_many_with_options(Self::select(), &(), &Self::options())
+            .await
+    }
+
+    /// the rows matching the `WHERE` expression, its `?` bound to the params
+    pub async fn select_where<P: BindParameters>(&self, expr: &str, params: &P) -> Result<Vec<T::ClickhouseDataType>, DatabaseError>
+    where
+        T::ClickhouseDataType: DbRow + DatabaseQuery<DB::Backend>
+    {
+        self.database
+            .query_many_with_options(format!("{} WHERE {expr}", Self::select()), params, &Self::options())
+            .await
+    }
+
+    /// deletes the rows matching the `WHERE` expression, its `?` bound to the
+    /// params, from the [`storage_table`](ClickhouseTable::storage_table).
+    /// fails with [`ClickhouseError::NoStorageTable`] for tables that store
+    /// nothing
+    pub async fn delete_where<P: BindParameters>(&self, expr: &str, params: &P) -> Result<(), DatabaseError> {
+        let table = T::storage_table().ok_or_else(|| ClickhouseError::NoStorageTable(T::full_name()))?;
+
+        self.database
+            .execute_remote_with_options(on_cluster(&format!("DELETE FROM {table} WHERE {expr}"), D::CLUSTER), params, &Self::options())
+            .await
+    }
+
+    pub async fn exists(&self) -> Result<bool, DatabaseError>
+    where
+        u8: DatabaseQuery<DB::Backend>
+    {
+        let exists = self
+            .database
+            .query_one_with_options::<u8, _>(format!("EXISTS {} {}", T::object_kind(), T::full_name()), &(), &Self::options())
+            .await?;
+
+        Ok(exists == 1)
+    }
+
+    /// the queries of the table are labeled with it in the metrics
+    fn options() -> QueryOptions {
+        QueryOptions::new().with_table::<T>()
+    }
+
+    /// `SELECT <the columns of the rows> FROM <the table>`
+    fn select() -> String
+    where
+        T::ClickhouseDataType: DbRow
+    {
+        let columns = <T::ClickhouseDataType as DbRow>::COLUMN_NAMES;
+        let columns = if columns.is_empty() {
+            "*".to_string()
+        } else {
+            columns
+                .iter()
+                .map(|column| format!("`{column}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        format!("SELECT {columns} FROM {}", T::full_name())
+    }
+}
+
+impl<D> ClickhouseClient<D>
+where
+    D: ClickhouseDBMS + Send + Sync + 'static
+{
+    /// the [`TableHandle`] of the table
+    pub fn table<T>(&self) -> TableHandle<'_, T, D, Self>
+    where
+        T: ClickhouseTable<D> + DatabaseTable<DataType = <T as ClickhouseTable<D>>::ClickhouseDataType>
+    {
+        TableHandle::new(self)
+    }
+}
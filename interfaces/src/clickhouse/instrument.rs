@@ -0,0 +1,190 @@
+use std::{sync::OnceLock, time::Instant};
+
+use futures::Future;
+use regex::Regex;
+
+use super::errors::ClickhouseError;
+use crate::errors::DatabaseError;
+
+/// max length of the query text recorded in the spans
+#[cfg(feature = "tracing")]
+const MAX_QUERY_LEN: usize = 256;
+
+/// kind of a [`Database`](crate::Database) call of the
+/// [`ClickhouseClient`](super::client::ClickhouseClient)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Insert,
+    Query,
+    QueryStream,
+    QueryRaw,
+    Execute
+}
+
+impl OperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationKind::Insert => "insert",
+            OperationKind::Query => "query",
+            OperationKind::QueryStream => "query_stream",
+            OperationKind::QueryRaw => "query_raw",
+            OperationKind::Execute => "execute"
+        }
+    }
+}
+
+/// a call being traced (with the `tracing` feature) and measured (with the
+/// `metrics` feature), a no-op without either of them
+#[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(dead_code))]
+pub(crate) struct Operation {
+    kind:  OperationKind,
+    /// the [`DatabaseTable::NAME`](crate::tables::DatabaseTable::NAME) of the
+    /// table, or `other`. only known names label the metrics, the table parsed
+    /// from the query is only recorded in the span
+    table: &'static str,
+    start: Instant,
+    #[cfg(feature = "tracing")]
+    span:  tracing::Span
+}
+
+#[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(unused_variables))]
+impl Operation {
+    /// an insert into the table, named by its
+    /// [`DatabaseTable::NAME`](crate::tables::DatabaseTable::NAME) if known
+    pub(crate) fn insert(table: &str, name: Option<&'static str>) -> Self {
+        Self::new(OperationKind::Insert, name.unwrap_or("other"), table, "")
+    }
+
+    /// a query on the table named by its
+    /// [`DatabaseTable::NAME`](crate::tables::DatabaseTable::NAME), if known.
+    /// the span has the first table the query reads from or writes to
+    pub(crate) fn query(kind: OperationKind, query: &str, name: Option<&'static str>) -> Self {
+        Self::new(kind, name.unwrap_or("other"), query_table(query).unwrap_or_default(), query)
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn new(kind: OperationKind, table: &'static str, span_table: &str, query: &str) -> Self {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "clickhouse",
+            operation = kind.as_str(),
+            table = span_table,
+            query = truncate(query, MAX_QUERY_LEN),
+            rows = tracing::field::Empty,
+            bytes = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+            error = tracing::field::Empty
+        );
+
+        Self {
+            kind,
+            table,
+            start: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span
+        }
+    }
+
+    /// runs the request in the span of the operation and records its outcome,
+    /// `size` gives the rows and (estimated) bytes written or read
+    pub(crate) async fn measure<T, F>(self, request: F, size: impl FnOnce(&T) -> (usize, usize)) -> Result<T, DatabaseError>
+    where
+        F: Future<Output = Result<T, DatabaseError>>
+    {
+        #[cfg(feature = "tracing")]
+        let request = tracing::Instrument::instrument(request, self.span.clone());
+
+        let res = request.await;
+
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        match &res {
+            Ok(value) => {
+                let (rows, bytes) = size(value);
+                self.finish(None, rows, bytes)
+            }
+            Err(error) => self.finish(Some(error), 0, 0)
+        }
+
+        res
+    }
+
+    /// records the outcome of the operation, `rows` and `bytes` are the rows
+    /// and (estimated) bytes written or read
+    pub(crate) fn finish(self, error: Option<&DatabaseError>, rows: usize, bytes: usize) {
+        let duration = self.start.elapsed();
+
+        #[cfg(feature = "tracing")]
+        self.record_span(error, rows, bytes, duration);
+
+        #[cfg(feature = "metrics")]
+        self.record_metrics(error, rows, bytes, duration);
+    }
+
+    #[cfg(feature = "tracing")]
+    fn record_span(&self, error: Option<&DatabaseError>, rows: usize, bytes: usize, duration: std::time::Duration) {
+        self.span.record("rows", rows);
+        self.span.record("bytes", bytes);
+        self.span.record("duration_ms", duration.as_millis() as u64);
+
+        if let Some(error) = error {
+            self.span.record("error", error_kind(error));
+            tracing::warn!(parent: &self.span, %error, "clickhouse {} failed", self.kind.as_str());
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_metrics(&self, error: Option<&DatabaseError>, rows: usize, bytes: usize, duration: std::time::Duration) {
+        let operation = self.kind.as_str();
+
+        metrics::histogram!("clickhouse_operation_duration_seconds", duration.as_secs_f64(), "operation" => operation, "table" => self.table);
+
+        if let Some(error) = error {
+            metrics::increment_counter!("clickhouse_errors_total", "operation" => operation, "table" => self.table, "error" => error_kind(error));
+            return
+        }
+
+        match self.kind {
+            OperationKind::Insert => {
+                metrics::increment_counter!("clickhouse_inserts_total", "table" => self.table);
+                metrics::counter!("clickhouse_rows_written_total", rows as u64, "table" => self.table);
+                metrics::counter!("clickhouse_bytes_written_total", bytes as u64, "table" => self.table);
+            }
+            _ => {
+                metrics::increment_counter!("clickhouse_queries_total", "operation" => operation, "table" => self.table);
+                metrics::counter!("clickhouse_rows_read_total", rows as u64, "operation" => operation, "table" => self.table);
+                metrics::counter!("clickhouse_bytes_read_total", bytes as u64, "operation" => operation, "table" => self.table);
+            }
+        }
+    }
+}
+
+/// the variant of the error, e.g. `too_many_parts`
+pub fn error_kind(error: &DatabaseError) -> &'static str {
+    error
+        .clickhouse_error()
+        .map_or("other", ClickhouseError::kind)
+}
+
+/// the first table the query reads from or writes to
+pub fn query_table(query: &str) -> Option<&str> {
+    static TABLE: OnceLock<Regex> = OnceLock::new();
+
+    TABLE
+        .get_or_init(|| Regex::new(r#"(?i)\b(?:FROM|INTO|TABLE|JOIN)\s+([\w.`"]+)"#).unwrap())
+        .captures(query)
+        .and_then(|captures| captures.get(1))
+        .map(|table| table.as_str())
+}
+
+/// the query up to `max_len` bytes, cut at a char boundary
+pub fn truncate(query: &str, max_len: usize) -> &str {
+    if query.len() <= max_len {
+        return query
+    }
+
+    let end = (0..=max_len)
+        .rev()
+        .find(|i| query.is_char_boundary(*i))
+        .unwrap_or(0);
+    &query[..end]
+}
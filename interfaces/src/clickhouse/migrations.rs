@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::OnceLock
+};
+
+use clickhouse::Row;
+use regex::Regex;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use super::{client::ClickhouseClient, dbms::ClickhouseDBMS, errors::ClickhouseError};
+use crate::{errors::DatabaseError, Database};
+
+/// table the applied migrations are recorded in, by default
+pub const MIGRATIONS_TABLE: &str = "default.schema_migrations";
+
+/// a migration file named `<version>_<name>.sql`, e.g. `0001_add_column.sql`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub version:  u64,
+    pub name:     String,
+    pub sql:      String,
+    /// hex encoded sha256 of the file
+    pub checksum: String
+}
+
+impl Migration {
+    pub fn new(version: u64, name: impl Into<String>, sql: impl Into<String>) -> Self {
+        let sql = sql.into();
+        let checksum = Sha256::digest(sql.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        Self { version, name: name.into(), sql, checksum }
+    }
+
+    /// the migrations of the directory ordered by version, files that don't end
+    /// in `.sql` are ignored
+    pub fn read_dir(dir: impl AsRef<Path>) -> Result<Vec<Self>, ClickhouseError> {
+        let mut migrations = Vec::new();
+        let mut files = HashMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(true, |ext| ext != "sql") {
+                continue
+            }
+
+            let file = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let (version, name) = parse_file_name(&file)
+                .ok_or_else(|| ClickhouseError::InvalidMigration { file: file.clone(), reason: "not named `<version>_<name>.sql`".to_string() })?;
+
+            if let Some(other) = files.insert(version, file.clone()) {
+                return Err(ClickhouseError::InvalidMigration { file, reason: format!("has the same version as `{other}`") })
+            }
+
+            migrations.push(Self::new(version, name, std::fs::read_to_string(&path)?));
+        }
+
+        migrations.sort_by_key(|migration| migration.version);
+
+        Ok(migrations)
+    }
+
+    /// the statements of the migration, each sent as its own request
+    pub fn statements(&self) -> Vec<&str> {
+        split_statements(&self.sql)
+    }
+}
+
+/// a migration recorded in the migrations table
+#[derive(Debug, Clone, PartialEq, Eq, Row, Deserialize)]
+pub struct AppliedMigration {
+    pub version:  u64,
+    pub name:     String,
+    pub checksum: String
+}
+
+/// applies the migrations of a directory that haven't been applied yet, in
+/// order of version
+///
+/// a migration is recorded once all of its statements succeeded, a migration
+/// that fails midway is retried from its first statement on the next run so
+/// its statements should be idempotent (`IF NOT EXISTS`, ...)
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    pub dir:   PathBuf,
+    /// table the applied migrations are recorded in
+    pub table: String
+}
+
+impl Migrator {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), table: MIGRATIONS_TABLE.to_string() }
+    }
+
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// the migrations that would be applied, without applying them
+    ///
+    /// fails if a migration that was already applied changed or is missing
+    /// from the directory
+    pub async fn pending<D: ClickhouseDBMS>(&self, client: &ClickhouseClient<D>) -> Result<Vec<Migration>, DatabaseError> {
+        let migrations = Migration::read_dir(&self.dir)?;
+
+        let applied = client
+            .query_many::<AppliedMigration, _>(format!("SELECT version, name, checksum FROM {} ORDER BY version", self.table), &())
+            .await;
+        let applied = match applied {
+            // nothing was ever applied
+            Err(DatabaseError::ClickhouseError(ClickhouseError::UnknownTable { .. })) => Vec::new(),
+            applied => applied?
+        };
+
+        Ok(pending_migrations(migrations, applied)?)
+    }
+
+    /// applies the pending migrations, `ON CLUSTER` if the DBMS has a cluster,
+    /// returning the ones that were applied
+    pub async fn run<D: ClickhouseDBMS>(&self, client: &ClickhouseClient<D>) -> Result<Vec<Migration>, DatabaseError> {
+        self.create_table(client).await?;
+        let pending = self.pending(client).await?;
+
+        for migration in &pending {
+            for statement in migration.statements() {
+                client
+                    .execute_remote(on_cluster(statement, D::CLUSTER), &())
+                    .await?;
+            }
+
+            client
+                .execute_remote(
+                    format!("INSERT INTO {} (version, name, checksum) VALUES (?, ?, ?)", self.table),
+                    &(migration.version, migration.name.clone(), migration.checksum.clone())
+                )
+                .await?;
+        }
+
+        Ok(pending)
+    }
+
+    async fn create_table<D: ClickhouseDBMS>(&self, client: &ClickhouseClient<D>) -> Result<(), DatabaseError> {
+        // replicated on a cluster so every node sees the same migrations
+        let engine = if D::CLUSTER.is_some() { "ReplicatedMergeTree" } else { "MergeTree" };
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {} (version UInt64, name String, checksum String, applied_at DateTime64(3) DEFAULT now64(3)) ENGINE = \
+             {engine} ORDER BY version",
+            self.table
+        );
+
+        client
+            .execute_remote(on_cluster(&create, D::CLUSTER), &())
+            .await
+    }
+}
+
+impl<D> ClickhouseClient<D>
+where
+    D: ClickhouseDBMS
+{
+    /// applies the pending migrations of the directory, see [`Migrator`]
+    pub async fn migrate(&self, dir: impl Into<PathBuf>) -> Result<Vec<Migration>, DatabaseError> {
+        Migrator::new(dir).run(self).await
+    }
+
+    /// the migrations of the directory [`ClickhouseClient::migrate`] would
+    /// apply
+    pub async fn migrate_dry_run(&self, dir: impl Into<PathBuf>) -> Result<Vec<Migration>, DatabaseError> {
+        Migrator::new(dir).pending(self).await
+    }
+}
+
+/// the migrations that weren't applied, checking the applied ones didn't
+/// change
+pub fn pending_migrations(migrations: Vec<Migration>, applied: Vec<AppliedMigration>) -> Result<Vec<Migration>, ClickhouseError> {
+    let mut applied = applied
+        .into_iter()
+        .map(|migration| (migration.version, migration))
+        .collect::<HashMap<_, _>>();
+
+    let mut pending = Vec::new();
+    for migration in migrations {
+        match applied.remove(&migration.version) {
+            Some(recorded) if recorded.checksum != migration.checksum || recorded.name != migration.name => {
+                return Err(ClickhouseError::MigrationChanged { version: migration.version, name: migration.name })
+            }
+            Some(_) => (),
+            None => pending.push(migration)
+        }
+    }
+
+    if let Some(missing) = applied
+        .into_values()
+        .min_by_key(|migration| migration.version)
+    {
+        return Err(ClickhouseError::MigrationMissing { version: missing.version, name: missing.name })
+    }
+
+    Ok(pending)
+}
+
+/// `(0001, add_column)` for `0001_add_column.sql`
+fn parse_file_name(file: &str) -> Option<(u64, String)> {
+    let (version, name) = file.strip_suffix(".sql")?.split_once('_')?;
+    if name.is_empty() {
+        return None
+    }
+
+    Some((version.parse().ok()?, name.to_string()))
+}
+
+/// adds `ON CLUSTER` after the object name of DDL statements that don't have
+/// it there
+pub fn on_cluster(statement: &str, cluster: Option<&str>) -> String {
+    static DDL: OnceLock<Regex> = OnceLock::new();
+
+    let Some(cluster) = cluster else { return statement.to_string() };
+
+    let ddl = DDL.get_or_init(|| {
+        Regex::new(
+            r#"(?i)^\s*(?:ALTER\s+TABLE|CREATE\s+(?:OR\s+REPLACE\s+)?(?:TABLE|(?:MATERIALIZED\s+)?VIEW|DICTIONARY|DATABASE)|DROP\s+(?:TABLE|VIEW|DICTIONARY|DATABASE)|TRUNCATE\s+TABLE|OPTIMIZE\s+TABLE|DELETE\s+FROM)\s+(?:IF\s+(?:NOT\s+)?EXISTS\s+)?[\w.`"]+(\s+ON\s+CLUSTER\b)?"#
+        )
+        .unwrap()
+    });
+
+    match ddl.captures(statement) {
+        Some(captures) if captures.get(1).is_none() => {
+            let object = captures.get(0).unwrap();
+            format!("{} ON CLUSTER {cluster}{}", object.as_str(), &statement[object.end()..])
+        }
+        _ => statement.to_string()
+    }
+}
+
+/// splits the sql on the `;` that aren't in a quoted string or a comment,
+/// dropping the empty statements
+pub fn split_statements(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    // skips escaped chars
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b';' => {
+                statements.push(&sql[start..i]);
+                start = i + 1;
+            }
+            _ => ()
+        }
+        i += 1;
+    }
+    statements.push(&sql[start.min(sql.len())..]);
+
+    statements
+        .into_iter()
+        .map(strip_leading_comments)
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
+/// the statement without the whitespace and `--` comment lines before it
+fn strip_leading_comments(mut statement: &str) -> &str {
+    loop {
+        statement = statement.trim();
+        match statement.strip_prefix("--") {
+            Some(comment) => statement = comment.split_once('\n').map_or("", |(_, rest)| rest),
+            None => return statement
+        }
+    }
+}
@@ -1,7 +1,17 @@
+pub mod bootstrap;
+pub mod cancel;
 pub mod client;
 pub mod config;
 pub mod dbms;
+pub mod endpoints;
 pub mod errors;
+pub mod handle;
+pub mod instrument;
+pub mod migrations;
+pub mod query_builder;
+pub mod retry;
+pub mod schema;
+pub mod shared;
 pub mod tables;
 pub mod types;
 pub mod utils;
@@ -0,0 +1,315 @@
+use std::fmt::Write;
+
+use clickhouse::query::Query;
+
+use super::{dbms::ClickhouseDBMS, errors::ClickhouseError, tables::ClickhouseTable};
+use crate::params::{BindParameters, ParamValues};
+
+/// `SELECT` query rendered from its parts. identifiers are quoted, values are
+/// bound to the `?` of the conditions through [`BindParameters`]. the first
+/// invalid part (e.g. a NaN sample ratio) is returned by [`SelectQuery::build`]
+///
+/// ```ignore
+/// let (sql, params) = SelectQuery::from_table::<Database1Table0_5, Dbms0>()
+///     .columns(["type0", "type1"])
+///     .filter("type1 > ?", 10u64)
+///     .order_by("type1", Order::Desc)
+///     .limit(100)
+///     .build()?;
+/// let rows: Vec<Row> = client.query_many(sql, &params).await?;
+/// ```
+#[derive(Default)]
+pub struct SelectQuery {
+    columns:    Vec<String>,
+    from:       String,
+    is_final:   bool,
+    sample:     Option<f64>,
+    conditions: Vec<String>,
+    group_by:   Vec<String>,
+    order_by:   Vec<String>,
+    limit:      Option<u64>,
+    offset:     Option<u64>,
+    settings:   Vec<(String, SettingValue)>,
+    params:     Vec<Box<dyn BindParameters>>,
+    /// the first invalid part of the query
+    error:      Option<String>
+}
+
+impl SelectQuery {
+    /// selects from `<database>.<table>`, quoting both
+    pub fn from(database: &str, table: &str) -> Self {
+        Self { from: format!("{}.{}", quote_identifier(database), quote_identifier(table)), ..Default::default() }
+    }
+
+    /// selects from the table
+    pub fn from_table<T, D>() -> Self
+    where
+        T: ClickhouseTable<D>,
+        D: ClickhouseDBMS + Send + Sync + 'static
+    {
+        Self::from(T::DATABASE_NAME, T::TABLE_NAME.trim_matches('`'))
+    }
+
+    /// adds the columns, quoted. without columns every column is selected
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>
+    {
+        self.columns.extend(
+            columns
+                .into_iter()
+                .map(|column| quote_identifier(column.as_ref()))
+        );
+        self
+    }
+
+    /// adds an expression (e.g. `count() AS count`) to the selected columns, as
+    /// is: it must not contain user input
+    pub fn expr(mut self, expr: &str) -> Self {
+        self.columns.push(expr.to_string());
+        self
+    }
+
+    /// `FINAL`, merges the rows of the table's engine before returning them
+    pub fn with_final(mut self) -> Self {
+        self.is_final = true;
+        self
+    }
+
+    /// `SAMPLE <ratio>` of the rows, the ratio must be in `(0, 1]`
+    pub fn sample(mut self, ratio: f64) -> Self {
+        if ratio > 0.0 && ratio <= 1.0 {
+            self.sample = Some(ratio);
+        } else {
+            self.invalid(format!("sample ratio {ratio} isn't in (0, 1]"));
+        }
+        self
+    }
+
+    /// adds a `WHERE` condition, and-ed with the others. its `?` are bound to
+    /// the params, in order
+    pub fn filter<P: BindParameters + 'static>(mut self, condition: &str, params: P) -> Self {
+        self.conditions.push(format!("({condition})"));
+        self.params.push(Box::new(params));
+        self
+    }
+
+    /// adds the `<column> = ?` condition, quoting the column
+    pub fn filter_eq<P: BindParameters + 'static>(self, column: &str, value: P) -> Self {
+        let condition = format!("{} = ?", quote_identifier(column));
+        self.filter(&condition, value)
+    }
+
+    /// adds a `GROUP BY` column, quoted
+    pub fn group_by(mut self, column: &str) -> Self {
+        self.group_by.push(quote_identifier(column));
+        self
+    }
+
+    /// adds an `ORDER BY` column, quoted
+    pub fn order_by(mut self, column: &str, order: Order) -> Self {
+        self.order_by
+            .push(format!("{} {}", quote_identifier(column), order.as_str()));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// adds a query level setting, e.g. `max_threads`. float values must be
+    /// finite
+    pub fn setting(mut self, name: &str, value: impl Into<SettingValue>) -> Self {
+        match value.into() {
+            SettingValue::Float(float) if !float.is_finite() => self.invalid(format!("setting {name} = {float} isn't a finite number")),
+            value => self.settings.push((quote_identifier(name), value))
+        }
+        self
+    }
+
+    /// the sql of the query, or the first invalid part of it
+    pub fn sql(&self) -> Result<String, ClickhouseError> {
+        if let Some(error) = &self.error {
+            return Err(ClickhouseError::InvalidQuery(error.clone()))
+        }
+
+        let mut sql = "SELECT ".to_string();
+        if self.columns.is_empty() {
+            sql.push('*');
+        } else {
+            sql.push_str(&self.columns.join(", "));
+        }
+
+        write!(sql, " FROM {}", self.from).unwrap();
+        if self.is_final {
+            sql.push_str(" FINAL");
+        }
+        if let Some(ratio) = self.sample {
+            write!(sql, " SAMPLE {ratio}").unwrap();
+        }
+        if !self.conditions.is_empty() {
+            write!(sql, " WHERE {}", self.conditions.join(" AND ")).unwrap();
+        }
+        if !self.group_by.is_empty() {
+            write!(sql, " GROUP BY {}", self.group_by.join(", ")).unwrap();
+        }
+        if !self.order_by.is_empty() {
+            write!(sql, " ORDER BY {}", self.order_by.join(", ")).unwrap();
+        }
+        if let Some(limit) = self.limit {
+            write!(sql, " LIMIT {limit}").unwrap();
+        }
+        if let Some(offset) = self.offset {
+            write!(sql, " OFFSET {offset}").unwrap();
+        }
+        if !self.settings.is_empty() {
+            let settings = self
+                .settings
+                .iter()
+                .map(|(name, value)| format!("{name} = {}", value.to_sql()))
+                .collect::<Vec<_>>();
+            write!(sql, " SETTINGS {}", settings.join(", ")).unwrap();
+        }
+
+        Ok(sql)
+    }
+
+    /// the sql of the query and the params bound to its `?`, or the first
+    /// invalid part of it
+    pub fn build(self) -> Result<(String, QueryParams), ClickhouseError> {
+        Ok((self.sql()?, QueryParams(self.params)))
+    }
+
+    /// keeps the first invalid part of the query
+    fn invalid(&mut self, reason: String) {
+        self.error.get_or_insert(reason);
+    }
+}
+
+/// the params of the conditions of a [`SelectQuery`], bound in order
+pub struct QueryParams(Vec<Box<dyn BindParameters>>);
+
+impl BindParameters for QueryParams {
+    fn bind_query(&self, query: Query) -> Query {
+        self.0
+            .iter()
+            .fold(query, |query, params| params.bind_query(query))
+    }
+
+    fn param_values(&self) -> ParamValues {
+        let mut values = Vec::new();
+        for params in &self.0 {
+            values.extend(params.param_values()?);
+        }
+
+        Ok(values)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc
+}
+
+impl Order {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Order::Asc => "ASC",
+            Order::Desc => "DESC"
+        }
+    }
+}
+
+/// the value of a setting of a [`SelectQuery`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bool(bool),
+    String(String)
+}
+
+impl SettingValue {
+    fn to_sql(&self) -> String {
+        match self {
+            SettingValue::Int(value) => value.to_string(),
+            SettingValue::UInt(value) => value.to_string(),
+            SettingValue::Float(value) => value.to_string(),
+            SettingValue::Bool(value) => u8::from(*value).to_string(),
+            SettingValue::String(value) => quote_string(value)
+        }
+    }
+}
+
+impl From<i64> for SettingValue {
+    fn from(value: i64) -> Self {
+        SettingValue::Int(value)
+    }
+}
+
+impl From<i32> for SettingValue {
+    fn from(value: i32) -> Self {
+        SettingValue::Int(value.into())
+    }
+}
+
+impl From<u64> for SettingValue {
+    fn from(value: u64) -> Self {
+        SettingValue::UInt(value)
+    }
+}
+
+impl From<f64> for SettingValue {
+    fn from(value: f64) -> Self {
+        SettingValue::Float(value)
+    }
+}
+
+impl From<bool> for SettingValue {
+    fn from(value: bool) -> Self {
+        SettingValue::Bool(value)
+    }
+}
+
+impl From<&str> for SettingValue {
+    fn from(value: &str) -> Self {
+        SettingValue::String(value.to_string())
+    }
+}
+
+impl From<String> for SettingValue {
+    fn from(value: String) -> Self {
+        SettingValue::String(value)
+    }
+}
+
+/// the identifier as is if it's a plain one (`[A-Za-z_][A-Za-z0-9_]*`),
+/// backtick quoted otherwise, e.g. `` `sub_db0.table0_3` ``
+pub fn quote_identifier(identifier: &str) -> String {
+    let plain = identifier
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        return identifier.to_string()
+    }
+
+    format!("`{}`", identifier.replace('\\', "\\\\").replace('`', "\\`"))
+}
+
+/// the single quoted string literal of the value
+pub fn quote_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
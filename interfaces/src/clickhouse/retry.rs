@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use futures::Future;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{
+    errors::{codes, ClickhouseError},
+    utils::deserialize_millis
+};
+use crate::errors::DatabaseError;
+
+/// server exceptions that are expected to succeed when retried
+const RETRYABLE_CODES: &[u32] = &[
+    codes::TOO_MANY_PARTS,
+    codes::TOO_MANY_SIMULTANEOUS_QUERIES,
+    codes::TIMEOUT_EXCEEDED,
+    codes::SOCKET_TIMEOUT,
+    codes::NETWORK_ERROR,
+    codes::ALL_CONNECTION_TRIES_FAILED,
+    codes::ALL_REPLICAS_ARE_STALE,
+    codes::NO_REPLICA_HAS_PART,
+    codes::REPLICA_IS_NOT_IN_QUORUM,
+    codes::TABLE_IS_READ_ONLY,
+    codes::KEEPER_EXCEPTION
+];
+
+/// responses from a proxy or load balancer in front of the server
+const RETRYABLE_STATUSES: &[&str] = &["502 Bad Gateway", "503 Service Unavailable", "504 Gateway Timeout"];
+
+/// how failed requests of the
+/// [`ClickhouseClient`](super::client::ClickhouseClient) are retried
+///
+/// when deserialized, missing fields are taken from the default policy and the
+/// backoffs are in milliseconds (`initial_backoff_ms`, `max_backoff_ms`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// attempts per request, including the first one. `1` disables retries
+    pub max_attempts:        u32,
+    /// backoff before the first retry
+    #[serde(rename = "initial_backoff_ms", deserialize_with = "deserialize_millis")]
+    pub initial_backoff:     Duration,
+    /// backoff is never longer than this
+    #[serde(rename = "max_backoff_ms", deserialize_with = "deserialize_millis")]
+    pub max_backoff:         Duration,
+    /// the backoff is multiplied by this after every retry
+    pub multiplier:          f64,
+    /// fraction of the backoff that's randomized, `0.0` is no jitter and `1.0`
+    /// sleeps anywhere between 0 and the full backoff
+    pub jitter:              f64,
+    /// sends an `insert_deduplication_token` with inserts so a retried insert
+    /// whose first attempt was written isn't inserted twice
+    pub deduplicate_inserts: bool,
+    /// decides if an error is retried
+    #[serde(skip)]
+    pub classifier:          fn(&DatabaseError) -> bool
+}
+
+impl RetryPolicy {
+    /// never retries
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Default::default() }
+    }
+
+    pub fn new(max_attempts: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self { max_attempts, initial_backoff, max_backoff, ..Default::default() }
+    }
+
+    pub fn with_classifier(mut self, classifier: fn(&DatabaseError) -> bool) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = clamp_fraction(jitter);
+        self
+    }
+
+    /// backoff before the retry following the `attempt`th attempt. an invalid
+    /// multiplier (NaN, negative) or an overflowing backoff gives the
+    /// `max_backoff`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = (self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent)).min(self.max_backoff.as_secs_f64());
+        let backoff = Duration::try_from_secs_f64(backoff).unwrap_or(self.max_backoff);
+
+        let jitter = backoff.mul_f64(clamp_fraction(self.jitter));
+        if jitter.is_zero() {
+            return backoff
+        }
+
+        backoff - rand::thread_rng().gen_range(Duration::ZERO..=jitter)
+    }
+
+    /// runs the request until it succeeds, fails with an error that isn't
+    /// retryable or runs out of attempts
+    pub async fn run<T, F, Fut>(&self, mut f: F) -> Result<T, DatabaseError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, DatabaseError>>
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(e) if attempt < self.max_attempts && (self.classifier)(&e) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                res => return res
+            }
+        }
+    }
+}
+
+/// the value clamped to `[0.0, 1.0]`, `0.0` if it's NaN
+fn clamp_fraction(value: f64) -> f64 {
+    if value.is_nan() {
+        return 0.0
+    }
+
+    value.clamp(0.0, 1.0)
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts:        3,
+            initial_backoff:     Duration::from_millis(100),
+            max_backoff:         Duration::from_secs(10),
+            multiplier:          2.0,
+            jitter:              0.5,
+            deduplicate_inserts: true,
+            classifier:          is_retryable
+        }
+    }
+}
+
+/// the default classifier, retries network errors, timeouts, proxy errors and
+/// server errors that are transient (too many parts, unavailable replicas,
+/// ...)
+pub fn is_retryable(error: &DatabaseError) -> bool {
+    match error {
+        DatabaseError::ClickhouseError(ClickhouseError::ClickhouseNative(error)) => match error {
+            clickhouse::error::Error::Network(_) | clickhouse::error::Error::TimedOut => true,
+            clickhouse::error::Error::BadResponse(response) => RETRYABLE_STATUSES
+                .iter()
+                .any(|status| response.contains(status)),
+            _ => false
+        },
+        DatabaseError::ClickhouseError(error) => error
+            .code()
+            .is_some_and(|code| RETRYABLE_CODES.contains(&code)),
+        _ => false
+    }
+}
@@ -0,0 +1,493 @@
+use std::{fmt, sync::OnceLock};
+
+use clickhouse::{DbRow, Row};
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{
+    client::ClickhouseClient,
+    dbms::ClickhouseDBMS,
+    errors::{codes, ClickhouseError},
+    tables::ClickhouseTable
+};
+use crate::{errors::DatabaseError, Database};
+
+/// clauses of a column definition that come after its type
+const COLUMN_CLAUSES: &[&str] = &["DEFAULT", "MATERIALIZED", "ALIAS", "EPHEMERAL", "CODEC", "TTL", "COMMENT", "SETTINGS"];
+
+/// a column of a live table, from `system.columns`
+#[derive(Debug, Clone, PartialEq, Eq, Row, Deserialize)]
+pub struct LiveColumn {
+    pub name:         String,
+    pub column_type:  String,
+    /// `DEFAULT`, `MATERIALIZED`, `ALIAS`, `EPHEMERAL` or empty
+    pub default_kind: String
+}
+
+impl LiveColumn {
+    pub fn new(name: impl Into<String>, column_type: impl Into<String>) -> Self {
+        Self { name: name.into(), column_type: column_type.into(), default_kind: String::new() }
+    }
+
+    pub fn with_default_kind(mut self, default_kind: impl Into<String>) -> Self {
+        self.default_kind = default_kind.into();
+        self
+    }
+
+    /// `MATERIALIZED` and `ALIAS` columns are computed by the server, the rows
+    /// don't have them
+    pub fn is_computed(&self) -> bool {
+        matches!(self.default_kind.as_str(), "MATERIALIZED" | "ALIAS")
+    }
+}
+
+/// a column whose type in the live table isn't the one of the table's sql file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub column:   String,
+    /// type in the sql file
+    pub expected: String,
+    /// type in the live table
+    pub actual:   String
+}
+
+/// differences between the columns of the rows of a table and the live table,
+/// empty when they match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// `<DATABASE NAME>.<TABLE NAME>`
+    pub table:           String,
+    /// columns of the rows the live table doesn't have
+    pub missing:         Vec<String>,
+    /// columns of the live table the rows don't have (computed columns aside)
+    pub extra:           Vec<String>,
+    /// columns both have but not in the same order
+    pub reordered:       Vec<String>,
+    pub type_mismatches: Vec<TypeMismatch>
+}
+
+impl SchemaDiff {
+    /// compares the column names of the rows (in order, empty for rows that
+    /// aren't structs) and the columns of the table's sql file to the live
+    /// columns
+    pub fn new(table: impl Into<String>, row_columns: &[&str], sql_columns: &[(String, String)], live: &[LiveColumn]) -> Self {
+        let mut diff = Self { table: table.into(), ..Default::default() };
+
+        let live_columns = live
+            .iter()
+            .filter(|column| !column.is_computed())
+            .map(|column| column.name.as_str())
+            .collect::<Vec<_>>();
+
+        if !row_columns.is_empty() {
+            diff.missing = row_columns
+                .iter()
+                .filter(|column| !live.iter().any(|live| live.name == **column))
+                .map(|column| column.to_string())
+                .collect();
+
+            diff.extra = live_columns
+                .iter()
+                .filter(|column| !row_columns.contains(column))
+                .map(|column| column.to_string())
+                .collect();
+
+            let shared_row = row_columns
+                .iter()
+                .filter(|column| live_columns.contains(column));
+            let shared_live = live_columns
+                .iter()
+                .filter(|column| row_columns.contains(column));
+            diff.reordered = shared_row
+                .zip(shared_live)
+                .filter(|(row, live)| row != live)
+                .map(|(row, _)| row.to_string())
+                .collect();
+        }
+
+        diff.type_mismatches = sql_columns
+            .iter()
+            .filter_map(|(name, expected)| {
+                let actual = live.iter().find(|column| column.name == *name)?;
+                (normalize_type(expected) != normalize_type(&actual.column_type)).then(|| TypeMismatch {
+                    column:   name.clone(),
+                    expected: expected.clone(),
+                    actual:   actual.column_type.clone()
+                })
+            })
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.reordered.is_empty() && self.type_mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "{}: schema matches", self.table)
+        }
+
+        write!(f, "{}: schema drifted", self.table)?;
+        if !self.missing.is_empty() {
+            write!(f, ", missing columns: {}", self.missing.join(", "))?;
+        }
+        if !self.extra.is_empty() {
+            write!(f, ", extra columns: {}", self.extra.join(", "))?;
+        }
+        if !self.reordered.is_empty() {
+            write!(f, ", reordered columns: {}", self.reordered.join(", "))?;
+        }
+        for mismatch in &self.type_mismatches {
+            write!(f, ", `{}` is {} instead of {}", mismatch.column, mismatch.actual, mismatch.expected)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// first difference between the fields of a row and the columns of the table's
+/// sql file, checked at compile time by
+/// [`remote_clickhouse_table!`](crate::remote_clickhouse_table)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnMismatch {
+    None,
+    /// the column at this index of the sql file isn't a field of the row
+    Missing(usize),
+    /// the row has fields that aren't columns of the sql file
+    Extra,
+    /// the column at this index of the sql file isn't at the same index in the
+    /// row
+    Reordered(usize)
+}
+
+/// the column names of the row, empty if it doesn't have named fields
+pub const fn row_columns<T: DbRow>() -> &'static [&'static str] {
+    T::COLUMN_NAMES
+}
+
+/// compares the fields of a row to the columns of the sql file, the rows
+/// without named fields (e.g. `String`) always match
+pub const fn column_mismatch(row: &[&str], columns: &[&str]) -> ColumnMismatch {
+    if row.is_empty() {
+        return ColumnMismatch::None
+    }
+
+    let mut i = 0;
+    while i < columns.len() {
+        if !const_contains(row, columns[i]) {
+            return ColumnMismatch::Missing(i)
+        }
+        i += 1;
+    }
+
+    if row.len() > columns.len() {
+        return ColumnMismatch::Extra
+    }
+
+    let mut i = 0;
+    while i < columns.len() && i < row.len() {
+        if !const_str_eq(row[i], columns[i]) {
+            return ColumnMismatch::Reordered(i)
+        }
+        i += 1;
+    }
+
+    ColumnMismatch::None
+}
+
+/// a message built at compile time from strings only known then (e.g. the
+/// fields of a row), for the panics of the column checks
+pub struct ConstMessage {
+    bytes: [u8; 1024],
+    len:   usize
+}
+
+impl ConstMessage {
+    pub const fn new() -> Self {
+        Self { bytes: [0; 1024], len: 0 }
+    }
+
+    /// appends the string, dropped if it doesn't fit
+    pub const fn push(mut self, value: &str) -> Self {
+        let value = value.as_bytes();
+        if self.len + value.len() > self.bytes.len() {
+            return self
+        }
+
+        let mut i = 0;
+        while i < value.len() {
+            self.bytes[self.len + i] = value[i];
+            i += 1;
+        }
+        self.len += value.len();
+
+        self
+    }
+
+    pub const fn push_usize(self, value: usize) -> Self {
+        let mut digits = [0u8; 20];
+        let (mut start, mut value) = (digits.len(), value);
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break
+            }
+        }
+
+        let mut this = self;
+        while start < digits.len() {
+            if this.len < this.bytes.len() {
+                this.bytes[this.len] = digits[start];
+                this.len += 1;
+            }
+            start += 1;
+        }
+
+        this
+    }
+
+    pub const fn as_str(&self) -> &str {
+        let (bytes, _) = self.bytes.split_at(self.len);
+        // only whole strings and ascii digits are pushed
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+}
+
+impl Default for ConstMessage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const fn const_contains(values: &[&str], value: &str) -> bool {
+    let mut i = 0;
+    while i < values.len() {
+        if const_str_eq(values[i], value) {
+            return true
+        }
+        i += 1;
+    }
+
+    false
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false
+        }
+        i += 1;
+    }
+
+    true
+}
+
+impl<D> ClickhouseClient<D>
+where
+    D: ClickhouseDBMS + 'static
+{
+    /// compares the rows of the table to the live table, run it at startup to
+    /// find the columns that would fail the inserts
+    pub async fn check_schema<T>(&self) -> Result<SchemaDiff, DatabaseError>
+    where
+        T: ClickhouseTable<D>,
+        T::ClickhouseDataType: DbRow
+    {
+        T::check_schema(self).await
+    }
+
+    /// the tables of the DBMS whose schema drifted
+    pub async fn check_all_schemas(&self) -> Result<Vec<SchemaDiff>, DatabaseError> {
+        let mut drifted = Vec::new();
+        for table in D::all_tables() {
+            let diff = table.check_schema(self).await?;
+            if !diff.is_empty() {
+                drifted.push(diff);
+            }
+        }
+
+        Ok(drifted)
+    }
+
+    /// the columns of the live table, in order
+    pub async fn live_columns(&self, database: &str, table: &str) -> Result<Vec<LiveColumn>, DatabaseError> {
+        let database = database.trim_matches('`');
+        let table = table.trim_matches('`');
+
+        let columns = self
+            .query_many::<LiveColumn, _>(
+                "SELECT name, type, default_kind FROM system.columns WHERE database = ? AND table = ? ORDER BY position",
+                &(database.to_string(), table.to_string())
+            )
+            .await?;
+
+        if columns.is_empty() {
+            return Err(
+                ClickhouseError::UnknownTable { code: codes::UNKNOWN_TABLE, message: format!("table {database}.`{table}` doesn't exist") }.into()
+            )
+        }
+
+        Ok(columns)
+    }
+}
+
+/// the `(name, type)` of the columns of a `CREATE TABLE` statement, empty if
+/// it doesn't list its columns (`AS other_table`, views, ...)
+pub fn parse_columns(create_sql: &str) -> Vec<(String, String)> {
+    static CREATE_TABLE: OnceLock<Regex> = OnceLock::new();
+
+    let create_table = CREATE_TABLE.get_or_init(|| Regex::new(r"(?is)^\s*CREATE\s+(?:OR\s+REPLACE\s+)?TABLE\s+([^(]*?)\(").unwrap());
+    let Some(captures) = create_table.captures(create_sql) else { return Vec::new() };
+
+    // `CREATE TABLE t AS other ENGINE = ...(`
+    let name = captures.get(1).map_or("", |name| name.as_str());
+    if name
+        .split_whitespace()
+        .any(|word| word.eq_ignore_ascii_case("AS") || word.eq_ignore_ascii_case("ENGINE"))
+    {
+        return Vec::new()
+    }
+
+    let start = captures.get(0).map_or(0, |create| create.end());
+    let body = &create_sql[start..];
+    let body = &body[..closing_paren(body).unwrap_or(body.len())];
+
+    split_top_level(body, b',')
+        .into_iter()
+        .filter_map(parse_column)
+        .collect()
+}
+
+/// `(name, type)` of a column definition, `None` for indexes, projections and
+/// constraints
+fn parse_column(definition: &str) -> Option<(String, String)> {
+    let definition = definition.trim();
+    let first_word = definition.split_whitespace().next()?;
+    if ["INDEX", "PROJECTION", "CONSTRAINT"]
+        .iter()
+        .any(|keyword| first_word.eq_ignore_ascii_case(keyword))
+    {
+        return None
+    }
+
+    let (name, rest) = match definition.strip_prefix('`') {
+        Some(quoted) => quoted.split_once('`')?,
+        None => definition.split_once(char::is_whitespace)?
+    };
+
+    let rest = rest.trim_start();
+    let column_type = rest[..type_end(rest)].trim();
+    if column_type.is_empty() {
+        return None
+    }
+
+    Some((name.to_string(), column_type.to_string()))
+}
+
+/// end of the type of a column definition, i.e. the first top level clause
+/// after it
+fn type_end(definition: &str) -> usize {
+    let bytes = definition.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            quote @ (b'\'' | b'"' | b'`') => i = skip_quoted(bytes, i, quote),
+            c if depth == 0 && c.is_ascii_whitespace() => {
+                let next = definition[i..].trim_start();
+                let word = next
+                    .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .next()
+                    .unwrap_or_default();
+                if COLUMN_CLAUSES
+                    .iter()
+                    .any(|clause| word.eq_ignore_ascii_case(clause))
+                {
+                    return i
+                }
+            }
+            _ => ()
+        }
+        i += 1;
+    }
+
+    definition.len()
+}
+
+/// index of the `)` closing the list the string starts in
+fn closing_paren(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' if depth == 0 => return Some(i),
+            b')' => depth -= 1,
+            quote @ (b'\'' | b'"' | b'`') => i = skip_quoted(bytes, i, quote),
+            _ => ()
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// splits on the separators that aren't in parentheses or quotes
+fn split_top_level(s: &str, separator: u8) -> Vec<&str> {
+    let bytes = s.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            quote @ (b'\'' | b'"' | b'`') => i = skip_quoted(bytes, i, quote),
+            c if c == separator && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => ()
+        }
+        i += 1;
+    }
+    parts.push(&s[start.min(s.len())..]);
+
+    parts
+}
+
+/// index of the quote closing the one at `start`
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() && bytes[i] != quote {
+        // skips escaped chars
+        i += if bytes[i] == b'\\' { 2 } else { 1 };
+    }
+
+    i
+}
+
+/// the type without its whitespace, `Decimal(38, 18)` and `Decimal(38,18)` are
+/// the same type
+fn normalize_type(column_type: &str) -> String {
+    column_type.split_whitespace().collect()
+}
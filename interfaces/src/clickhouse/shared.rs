@@ -1,209 +1,451 @@
-use std::collections::HashMap;
+use std::{any::Any, collections::HashMap, pin::Pin, time::Duration};
+
+use futures::{future::join_all, Future, Stream};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::MissedTickBehavior
+};
+
+use super::{client::ClickhouseClient, dbms::ClickhouseDBMS, errors::ClickhouseError, types::ClickhouseBackend};
+use crate::{
+    errors::DatabaseError, inserts::estimated_size, options::QueryOptions, params::BindParameters, Database, DatabaseInsert, DatabaseQuery,
+    DatabaseTable
+};
+
+/// thresholds at which the buffered rows of a table are written to clickhouse
+#[derive(Debug, Clone)]
+pub struct BufferedInsertConfig {
+    /// flushes a table once it has buffered this many rows
+    pub max_rows:           usize,
+    /// flushes a table once its buffered rows are estimated to be this many
+    /// bytes
+    pub max_bytes:          usize,
+    /// flushes every table at this interval, regardless of size
+    pub flush_interval:     Duration,
+    /// number of sends the channel to the receiver holds, the senders wait
+    /// once it's full (e.g. while the server is down)
+    pub channel_capacity:   usize,
+    /// the receiver stops taking sends once every table together buffers this
+    /// many rows, until a flush writes them
+    pub max_buffered_rows:  usize,
+    /// the receiver stops taking sends once every table together buffers rows
+    /// estimated to be this many bytes, until a flush writes them
+    pub max_buffered_bytes: usize
+}
 
-use clickhouse::Client;
+impl BufferedInsertConfig {
+    pub fn new(max_rows: usize, max_bytes: usize, flush_interval: Duration) -> Self {
+        Self { max_rows, max_bytes, flush_interval, ..Default::default() }
+    }
 
-use super::{config::ClickhouseConfig, dbms::ClickhouseDBMS, errors::ClickhouseError, types::ClickhouseQuery};
-use crate::{errors::DatabaseError, BindParameters, Database, DatabaseTable};
+    pub fn with_channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = channel_capacity;
+        self
+    }
 
-pub struct BufferedClickhouseClientTx<'db, D: ClickhouseDBMS> {
-    pub client: &'db Client,
-    pub tx:     tokio::sync::mpsc::UnboundedSender<D::DataEnum>
+    pub fn with_max_buffered(mut self, max_buffered_rows: usize, max_buffered_bytes: usize) -> Self {
+        self.max_buffered_rows = max_buffered_rows;
+        self.max_buffered_bytes = max_buffered_bytes;
+        self
+    }
 }
 
-impl<D: ClickhouseDBMS> BufferedClickhouseClientTx<'_, D> {
-    pub fn send_to_buffer<T: Into<D::DataEnum>>(&self, value: T) -> Result<(), DatabaseError> {
-        self.tx
-            .send(value.into())
-            .map_err(|e| DatabaseError::from(ClickhouseError::SharedSendError(e.to_string())))
+impl Default for BufferedInsertConfig {
+    fn default() -> Self {
+        Self {
+            max_rows:           100_000,
+            max_bytes:          32 * 1024 * 1024,
+            flush_interval:     Duration::from_secs(5),
+            channel_capacity:   1024,
+            max_buffered_rows:  1_000_000,
+            max_buffered_bytes: 256 * 1024 * 1024
+        }
     }
 }
 
-#[async_trait::async_trait]
-impl<D> Database for BufferedClickhouseClientTx<'_, D>
+/// cloneable handle used to send rows for any table of the DBMS to the buffer
+///
+/// queries are passed straight through to the underlying client, inserts are
+/// buffered
+#[derive(Clone)]
+pub struct BufferedClickhouseClientTx<D> {
+    pub client: ClickhouseClient<D>,
+    tx:         mpsc::Sender<BufferedMessage<D>>
+}
+
+impl<D> BufferedClickhouseClientTx<D>
 where
-    D: ClickhouseDBMS
+    D: ClickhouseDBMS + Clone + 'static
 {
-    type DBMS = D;
+    /// creates the sending handle and the receiver that groups and writes the
+    /// rows, the receiver needs to be driven with
+    /// [`BufferedClickhouseClientRx::run`]
+    pub fn new(client: ClickhouseClient<D>, config: BufferedInsertConfig) -> (Self, BufferedClickhouseClientRx<D>) {
+        let (tx, rx) = mpsc::channel(config.channel_capacity.max(1));
+
+        let buffered_rx =
+            BufferedClickhouseClientRx { client: client.clone(), config, rx, buffers: HashMap::new(), error: None, flush_failed: false };
 
-    async fn insert_one<T: DatabaseTable>(&self, _value: &T::DataType) -> Result<(), DatabaseError> {
-        unreachable!("cannot insert on shared buffered clickhouse client");
+        (Self { client, tx }, buffered_rx)
     }
 
-    async fn insert_many<T: DatabaseTable>(&self, _values: &[T::DataType]) -> Result<(), DatabaseError> {
-        unreachable!("cannot insert on shared buffered clickhouse client");
+    /// creates the sending handle and spawns the receiver on the tokio runtime
+    pub fn spawn(client: ClickhouseClient<D>, config: BufferedInsertConfig) -> (Self, tokio::task::JoinHandle<Result<(), DatabaseError>>) {
+        let (this, rx) = Self::new(client, config);
+        (this, tokio::spawn(rx.run()))
     }
+}
 
-    async fn query_one<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<Q, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+impl<D> BufferedClickhouseClientTx<D>
+where
+    D: ClickhouseDBMS
+{
+    /// waits while the channel to the receiver is full
+    pub async fn send_to_buffer<T: DatabaseTable>(&self, value: T::DataType) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        self.send_many_to_buffer::<T>(vec![value]).await
+    }
+
+    /// waits while the channel to the receiver is full
+    pub async fn send_many_to_buffer<T: DatabaseTable>(&self, values: Vec<T::DataType>) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        if values.is_empty() {
+            return Ok(())
+        }
 
-        let res = query
-            .fetch_one::<Q>()
+        self.send(BufferedMessage::Rows { table: T::NAME, rows: Box::new(RowBuffer::new(values)) })
             .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+    }
+
+    /// writes every buffered row, returns the first error of any flush since
+    /// the last call
+    pub async fn flush(&self) -> Result<(), DatabaseError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(BufferedMessage::Flush(tx)).await?;
 
-        Ok(res)
+        rx.await
+            .map_err(|e| ClickhouseError::SharedSendError(e.to_string()))?
     }
 
-    async fn query_one_optional<Q: ClickhouseQuery, P: BindParameters>(
-        &self,
-        query: impl AsRef<str> + Send,
-        params: P
-    ) -> Result<Option<Q>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    /// drains every row sent before the call, writes them and stops the
+    /// receiver. rows that can't be written are also returned as an error by
+    /// [`BufferedClickhouseClientRx::run`]
+    pub async fn shutdown(self) -> Result<(), DatabaseError> {
+        let (tx, rx) = oneshot::channel();
+        self.send(BufferedMessage::Shutdown(tx)).await?;
 
-        let res = query
-            .fetch_optional::<Q>()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+        rx.await
+            .map_err(|e| ClickhouseError::SharedSendError(e.to_string()))?
+    }
 
-        Ok(res)
+    async fn send(&self, msg: BufferedMessage<D>) -> Result<(), DatabaseError> {
+        self.tx
+            .send(msg)
+            .await
+            .map_err(|e| DatabaseError::from(ClickhouseError::SharedSendError(e.to_string())))
     }
+}
+
+impl<D> Database for BufferedClickhouseClientTx<D>
+where
+    D: ClickhouseDBMS
+{
+    type Backend = ClickhouseBackend;
+    type DBMS = D;
 
-    async fn query_many<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<Vec<Q>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        self.send_to_buffer::<T>(dyn_clone::clone(value)).await
+    }
 
-        let res = query
-            .fetch_all::<Q>()
+    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        self.send_many_to_buffer::<T>(values.iter().map(dyn_clone::clone).collect())
             .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+    }
 
-        Ok(res)
+    async fn query_one<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Q, DatabaseError> {
+        self.client.query_one(query, params).await
     }
 
-    async fn query_raw<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<Vec<u8>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
-        query
-            .fetch_raw::<Q>()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))
+    async fn query_one_optional<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Option<Q>, DatabaseError> {
+        self.client.query_one_optional(query, params).await
     }
 
-    async fn execute_remote<P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<(), DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    async fn query_many<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<Q>, DatabaseError> {
+        self.client.query_many(query, params).await
+    }
 
-        query
-            .execute()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+    fn query_stream<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> impl Stream<Item = Result<Q, DatabaseError>> + Send {
+        self.client.query_stream(query, params)
+    }
 
-        Ok(())
+    async fn query_raw<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<u8>, DatabaseError> {
+        self.client.query_raw::<Q, P>(query, params).await
     }
-}
 
-pub struct BufferedClickhouseClientRx<'db, D: ClickhouseDBMS> {
-    pub client:    &'db Client,
-    pub rx:        tokio::sync::mpsc::UnboundedReceiver<D::DataEnum>,
-    pub value_map: HashMap<D, Vec<D::DataEnum>>
+    async fn execute_remote<P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<(), DatabaseError> {
+        self.client.execute_remote(query, params).await
+    }
 }
 
-impl<D: ClickhouseDBMS> BufferedClickhouseClientRx<'_, D> {
-    // pub fn add_to_values(&self, value: D::DataEnum) -> Result<(), DatabaseError>
-    // {     self.rx
-    //         .send(value.into())
-    //         .map_err(|e|
-    // DatabaseError::from(ClickhouseError::SharedSendError(e.to_string()))) }
+/// background receiver that groups the buffered rows per table and writes
+/// them once a threshold in [`BufferedInsertConfig`] is hit. once a write
+/// fails, the rows are only retried at the flush interval, and the senders wait
+/// while the buffers are full
+pub struct BufferedClickhouseClientRx<D> {
+    client:       ClickhouseClient<D>,
+    config:       BufferedInsertConfig,
+    rx:           mpsc::Receiver<BufferedMessage<D>>,
+    buffers:      HashMap<&'static str, TableBuffer<D>>,
+    /// first error from a flush not requested by a sender
+    error:        Option<DatabaseError>,
+    /// the last flush failed, its rows are retried at the next interval
+    flush_failed: bool
 }
 
-#[async_trait::async_trait]
-impl<D> Database for BufferedClickhouseClientRx<'_, D>
+impl<D> BufferedClickhouseClientRx<D>
 where
     D: ClickhouseDBMS
 {
-    type DBMS = D;
+    /// runs until [`BufferedClickhouseClientTx::shutdown`] is called or every
+    /// sender is dropped, writing all remaining rows before returning
+    pub async fn run(mut self) -> Result<(), DatabaseError> {
+        let mut interval = tokio::time::interval(self.config.flush_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                msg = self.rx.recv(), if !self.is_full() => match msg {
+                    Some(BufferedMessage::Shutdown(tx)) => {
+                        self.rx.close();
+                        while let Some(msg) = self.rx.recv().await {
+                            self.handle_message(msg).await;
+                        }
+
+                        let res = self.flush_all().await;
+                        let failed = res
+                            .as_ref()
+                            .err()
+                            .map(|e| DatabaseError::from(ClickhouseError::SharedSendError(format!("buffered rows not written on shutdown: {e}"))));
+
+                        return match tx.send(res) {
+                            // the caller stopped waiting, the error is only returned here
+                            Err(res) => res,
+                            Ok(()) => failed.map_or(Ok(()), Err)
+                        }
+                    }
+                    Some(msg) => self.handle_message(msg).await,
+                    None => return self.flush_all().await
+                },
+                _ = interval.tick() => {
+                    if let Err(e) = self.flush_tables(|_| true).await {
+                        self.error.get_or_insert(e);
+                    }
+                }
+            }
+        }
+    }
 
-    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError> {
-        let mut insert = self
-            .client
-            .insert(Self::DBMS::from_database_table_str(T::NAME).full_name())
-            .map_err(|e| DatabaseError::from(ClickhouseError::InsertError(e.to_string())))?;
+    async fn handle_message(&mut self, msg: BufferedMessage<D>) {
+        match msg {
+            BufferedMessage::Rows { table, rows } => {
+                if let Err(e) = self.buffer_rows(table, rows).await {
+                    self.error.get_or_insert(e);
+                }
+            }
+            BufferedMessage::Flush(tx) => {
+                let _ = tx.send(self.flush_all().await);
+            }
+            BufferedMessage::Shutdown(tx) => {
+                let _ = tx.send(Err(ClickhouseError::SharedSendError("buffered client is already shutting down".to_string()).into()));
+            }
+        }
+    }
 
-        insert
-            .write(value)
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::InsertError(e.to_string())))?;
+    async fn buffer_rows(&mut self, table: &'static str, rows: Box<dyn BufferedRows<D>>) -> Result<(), DatabaseError> {
+        let buffer = self
+            .buffers
+            .entry(table)
+            .or_insert_with(|| TableBuffer { full_name: D::from_database_table_str(table).full_name(), rows: rows.empty() });
+        buffer.rows.append(rows)?;
 
-        insert
-            .end()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::InsertError(e.to_string())))?;
+        if self.flush_failed {
+            return Ok(())
+        }
 
-        Ok(())
+        let (max_rows, max_bytes) = (self.config.max_rows, self.config.max_bytes);
+        self.flush_tables(|buffer| buffer.rows.len() >= max_rows || buffer.rows.size_bytes() >= max_bytes)
+            .await
     }
 
-    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError> {
-        let mut insert = self
-            .client
-            .insert(Self::DBMS::from_database_table_str(T::NAME).full_name())
-            .map_err(|e| DatabaseError::from(ClickhouseError::InsertError(e.to_string())))?;
+    async fn flush_all(&mut self) -> Result<(), DatabaseError> {
+        let res = self.flush_tables(|_| true).await;
 
-        for value in values {
-            insert
-                .write(value)
-                .await
-                .map_err(|e| DatabaseError::from(ClickhouseError::InsertError(e.to_string())))?;
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => res
         }
+    }
 
-        insert
-            .end()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::InsertError(e.to_string())))?;
+    /// writes every non-empty buffer matching the filter concurrently
+    async fn flush_tables(&mut self, filter: impl Fn(&TableBuffer<D>) -> bool) -> Result<(), DatabaseError> {
+        let client = &self.client;
+
+        let res = join_all(
+            self.buffers
+                .iter_mut()
+                .filter(|(_, buffer)| !buffer.rows.is_empty() && filter(buffer))
+                .map(|(name, TableBuffer { full_name, rows })| rows.write(client, *name, full_name))
+        )
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>();
+        self.flush_failed = res.is_err();
+
+        res.map(|_| ())
+    }
 
-        Ok(())
+    /// every table together buffers more rows than
+    /// [`max_buffered_rows`](BufferedInsertConfig::max_buffered_rows) or
+    /// [`max_buffered_bytes`](BufferedInsertConfig::max_buffered_bytes)
+    fn is_full(&self) -> bool {
+        let (rows, bytes) = self
+            .buffers
+            .values()
+            .fold((0, 0), |(rows, bytes), buffer| (rows + buffer.rows.len(), bytes + buffer.rows.size_bytes()));
+
+        rows >= self.config.max_buffered_rows || bytes >= self.config.max_buffered_bytes
     }
+}
 
-    async fn query_one<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<Q, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+enum BufferedMessage<D> {
+    Rows { table: &'static str, rows: Box<dyn BufferedRows<D>> },
+    Flush(oneshot::Sender<Result<(), DatabaseError>>),
+    Shutdown(oneshot::Sender<Result<(), DatabaseError>>)
+}
 
-        let res = query
-            .fetch_one::<Q>()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+struct TableBuffer<D> {
+    full_name: String,
+    rows:      Box<dyn BufferedRows<D>>
+}
+
+/// type erased rows of a single table
+trait BufferedRows<D>: Send {
+    fn len(&self) -> usize;
 
-        Ok(res)
+    fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
-    async fn query_one_optional<Q: ClickhouseQuery, P: BindParameters>(
-        &self,
-        query: impl AsRef<str> + Send,
-        params: P
-    ) -> Result<Option<Q>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    fn size_bytes(&self) -> usize;
 
-        let res = query
-            .fetch_optional::<Q>()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+    /// an empty buffer of the same row type
+    fn empty(&self) -> Box<dyn BufferedRows<D>>;
 
-        Ok(res)
-    }
+    fn append(&mut self, other: Box<dyn BufferedRows<D>>) -> Result<(), ClickhouseError>;
 
-    async fn query_many<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<Vec<Q>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send>;
 
-        let res = query
-            .fetch_all::<Q>()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+    /// inserts the buffered rows into the table, `name` is its
+    /// [`DatabaseTable::NAME`]. the rows are only removed once inserted, a
+    /// failed write keeps them for the next flush
+    fn write<'a>(
+        &'a mut self,
+        client: &'a ClickhouseClient<D>,
+        name: &'static str,
+        table: &'a str
+    ) -> Pin<Box<dyn Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
+}
+
+struct RowBuffer<R> {
+    rows:       Vec<R>,
+    size_bytes: usize
+}
+
+impl<R: DatabaseInsert<ClickhouseBackend>> RowBuffer<R> {
+    fn new(rows: Vec<R>) -> Self {
+        let size_bytes = rows.iter().map(estimated_size).sum();
+        Self { rows, size_bytes }
+    }
+}
 
-        Ok(res)
+impl<D, R> BufferedRows<D> for RowBuffer<R>
+where
+    D: ClickhouseDBMS,
+    R: DatabaseInsert<ClickhouseBackend>
+{
+    fn len(&self) -> usize {
+        self.rows.len()
     }
 
-    async fn query_raw<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<Vec<u8>, DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
-        query
-            .fetch_raw::<Q>()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))
+    fn size_bytes(&self) -> usize {
+        self.size_bytes
+    }
+
+    fn empty(&self) -> Box<dyn BufferedRows<D>> {
+        Box::new(RowBuffer::<R>::new(Vec::new()))
     }
 
-    async fn execute_remote<P: BindParameters>(&self, query: impl AsRef<str> + Send, params: P) -> Result<(), DatabaseError> {
-        let query = params.bind_query(self.client.query(query.as_ref()));
+    fn append(&mut self, other: Box<dyn BufferedRows<D>>) -> Result<(), ClickhouseError> {
+        let other = other
+            .into_any()
+            .downcast::<Self>()
+            .map_err(|_| ClickhouseError::SharedSendError(format!("buffered rows are not of type {}", std::any::type_name::<R>())))?;
 
-        query
-            .execute()
-            .await
-            .map_err(|e| DatabaseError::from(ClickhouseError::QueryError(e.to_string())))?;
+        self.size_bytes += other.size_bytes;
+        self.rows.extend(other.rows);
 
         Ok(())
     }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+
+    fn write<'a>(
+        &'a mut self,
+        client: &'a ClickhouseClient<D>,
+        name: &'static str,
+        table: &'a str
+    ) -> Pin<Box<dyn Future<Output = Result<(), DatabaseError>> + Send + 'a>> {
+        Box::pin(async move {
+            client
+                .insert_table_rows(Some(name), table, &self.rows, &QueryOptions::default())
+                .await?;
+
+            self.rows.clear();
+            self.size_bytes = 0;
+
+            Ok(())
+        })
+    }
 }
@@ -1,6 +1,19 @@
 #![allow(async_fn_in_trait)]
 
-use super::{client::ClickhouseClient, dbms::ClickhouseDBMS, errors::ClickhouseError, types::ClickhouseInsert};
+use std::sync::OnceLock;
+
+use clickhouse::DbRow;
+use regex::Regex;
+
+use super::{
+    bootstrap::{drop_statement, if_not_exists},
+    client::ClickhouseClient,
+    dbms::ClickhouseDBMS,
+    errors::ClickhouseError,
+    migrations::on_cluster,
+    schema::{parse_columns, SchemaDiff},
+    types::ClickhouseInsert
+};
 use crate::{errors::DatabaseError, Database};
 
 #[derive(Default, Debug, PartialEq, Eq, Clone)]
@@ -11,15 +24,45 @@ pub enum ClickhouseTableKind {
     ReplicatedMergeTree,
     ReplicatedAggregatingMergeTree,
     ReplicatedReplacingMergeTree,
+    ReplicatedSummingMergeTree,
+    ReplicatedCollapsingMergeTree,
+    ReplicatedVersionedCollapsingMergeTree,
     MergeTree,
     AggregatingMergeTree,
     ReplacingMergeTree,
+    SummingMergeTree,
+    CollapsingMergeTree,
+    VersionedCollapsingMergeTree,
     MaterializedView,
+    View,
+    Dictionary,
     Null,
+    Log,
+    Memory,
+    Join,
+    Set,
+    Buffer,
+    Kafka,
     #[default]
     None
 }
 
+/// the zookeeper path and replica of a `Replicated*` engine
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ReplicatedEngine {
+    pub zookeeper_path: &'static str,
+    pub replica:        &'static str
+}
+
+/// the arguments of a `Distributed` engine
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DistributedEngine {
+    pub cluster:      &'static str,
+    pub database:     &'static str,
+    pub table:        &'static str,
+    pub sharding_key: Option<&'static str>
+}
+
 /// trait for different implementations of clickhouse tables
 //#[async_trait::async_trait]
 pub trait ClickhouseTable<D>: Send + Sync
@@ -29,16 +72,27 @@ where
     const DATABASE_NAME: &'static str;
     const TABLE_NAME: &'static str;
     const FILE_PATH: &'static str;
+    /// the `CREATE` statement of the table, embedded at compile time
+    const CREATE_SQL: &'static str;
     const CHILD_TABLES: &'static [D];
     const TABLE_TYPE: ClickhouseTableKind;
     const TABLE_ENUM: D;
+    /// the zookeeper path and replica of `Replicated*` engines
+    const REPLICATION: Option<ReplicatedEngine> = None;
+    /// the cluster, table and sharding key of `Distributed` engines
+    const DISTRIBUTED: Option<DistributedEngine> = None;
+    /// the version column of `ReplacingMergeTree` engines
+    const VERSION_COLUMN: Option<&'static str> = None;
+    const ORDER_BY: Option<&'static str> = None;
+    const PARTITION_BY: Option<&'static str> = None;
+    const PRIMARY_KEY: Option<&'static str> = None;
+    const TTL: Option<&'static str> = None;
     type ClickhouseDataType: ClickhouseInsert;
 
     /// creates the table and associated tables
     fn create_table(database: &ClickhouseClient<D>) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
         async {
-            let table_sql_path = Self::FILE_PATH;
-            let create_sql = std::fs::read_to_string(table_sql_path).map_err(|e| ClickhouseError::SqlFileReadError(e.to_string()))?;
+            let create_sql = on_cluster(&Self::create_sql(database)?, D::CLUSTER);
             database.execute_remote(&create_sql, &()).await?;
 
             for table in Self::CHILD_TABLES {
@@ -49,6 +103,154 @@ where
         }
     }
 
+    /// creates the table if it doesn't exist, without its child tables
+    fn create_if_not_exists(database: &ClickhouseClient<D>) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
+        async {
+            let create_sql = if_not_exists(&on_cluster(&Self::create_sql(database)?, D::CLUSTER));
+            database.execute_remote(&create_sql, &()).await?;
+
+            Ok(())
+        }
+    }
+
+    /// compares the columns of the rows and of the sql file to the live table
+    fn check_schema(database: &ClickhouseClient<D>) -> impl std::future::Future<Output = Result<SchemaDiff, DatabaseError>> + Send
+    where
+        Self::ClickhouseDataType: DbRow
+    {
+        async {
+            // the types are only checked when the override can be read
+            let create_sql = Self::create_sql(database).unwrap_or_default();
+            let live = database
+                .live_columns(Self::DATABASE_NAME, Self::TABLE_NAME)
+                .await?;
+
+            Ok(SchemaDiff::new(Self::full_name(), <Self::ClickhouseDataType as DbRow>::COLUMN_NAMES, &parse_columns(&create_sql), &live))
+        }
+    }
+
+    /// whether the table exists
+    fn exists(database: &ClickhouseClient<D>) -> impl std::future::Future<Output = Result<bool, DatabaseError>> + Send {
+        async {
+            let exists = database
+                .query_one::<u8, _>(format!("EXISTS {} {}", Self::object_kind(), Self::full_name()), &())
+                .await?;
+
+            Ok(exists == 1)
+        }
+    }
+
+    /// removes the rows of the [`Self::storage_table`]
+    fn truncate(database: &ClickhouseClient<D>) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
+        async {
+            let Some(table) = Self::storage_table() else { return Ok(()) };
+            database
+                .execute_remote(on_cluster(&format!("TRUNCATE TABLE IF EXISTS {table}"), D::CLUSTER), &())
+                .await
+        }
+    }
+
+    /// drops the table if it exists, without its child tables
+    fn drop(database: &ClickhouseClient<D>) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
+        async {
+            database
+                .execute_remote(drop_statement(&Self::TABLE_ENUM), &())
+                .await
+        }
+    }
+
+    /// renames the table to `new_name` in its database. the constants of the
+    /// table still have the old name
+    fn rename(database: &ClickhouseClient<D>, new_name: &str) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
+        let mut rename = format!("RENAME {} {} TO {}.{new_name}", Self::object_kind(), Self::full_name(), Self::DATABASE_NAME);
+        if let Some(cluster) = D::CLUSTER {
+            rename.push_str(&format!(" ON CLUSTER {cluster}"));
+        }
+
+        async move { database.execute_remote(rename, &()).await }
+    }
+
+    /// merges the parts of the [`Self::storage_table`], of a single partition
+    /// when `partition` (an expression, e.g. `'2024-01'` or `ID '202401'`) is
+    /// set. `final_merge` merges them into one part even if they are already
+    /// merged
+    fn optimize(
+        database: &ClickhouseClient<D>,
+        final_merge: bool,
+        partition: Option<&str>
+    ) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
+        let optimize = Self::storage_table().map(|table| {
+            let mut optimize = on_cluster(&format!("OPTIMIZE TABLE {table}"), D::CLUSTER);
+            if let Some(partition) = partition {
+                optimize.push_str(&format!(" PARTITION {partition}"));
+            }
+            if final_merge {
+                optimize.push_str(" FINAL");
+            }
+            optimize
+        });
+
+        async move {
+            match optimize {
+                Some(optimize) => database.execute_remote(optimize, &()).await,
+                None => Ok(())
+            }
+        }
+    }
+
+    /// the `CREATE` statement of the live table
+    fn show_create(database: &ClickhouseClient<D>) -> impl std::future::Future<Output = Result<String, DatabaseError>> + Send {
+        async {
+            database
+                .query_one::<String, _>(format!("SHOW CREATE {} {}", Self::object_kind(), Self::full_name()), &())
+                .await
+        }
+    }
+
+    /// the table that stores the rows: the local table of `Distributed`
+    /// tables, the `TO` table of materialized views (the view itself when
+    /// it has an inner table), `None` for `Null` tables that store nothing
+    fn storage_table() -> Option<String> {
+        match Self::TABLE_TYPE {
+            ClickhouseTableKind::Null => None,
+            ClickhouseTableKind::Distributed => Some(match Self::DISTRIBUTED {
+                Some(engine) => format!("{}.{}", engine.database, engine.table),
+                None => Self::full_name()
+            }),
+            ClickhouseTableKind::MaterializedView => Some(view_target(Self::CREATE_SQL).unwrap_or_else(Self::full_name)),
+            _ => Some(Self::full_name())
+        }
+    }
+
+    /// `TABLE` or `DICTIONARY`, the object the statements of the table are on
+    fn object_kind() -> &'static str {
+        match Self::TABLE_TYPE {
+            ClickhouseTableKind::Dictionary => "DICTIONARY",
+            _ => "TABLE"
+        }
+    }
+
+    /// the `CREATE` statement of the table: the file named
+    /// [`Self::override_file_name`] in the
+    /// [`sql_override_dir`](super::config::ClickhouseConfig::sql_override_dir)
+    /// of the client if there is one, [`Self::CREATE_SQL`] otherwise
+    fn create_sql(database: &ClickhouseClient<D>) -> Result<String, ClickhouseError> {
+        let Some(dir) = &database.sql_override_dir else { return Ok(Self::CREATE_SQL.to_string()) };
+
+        let path = dir.join(Self::override_file_name());
+        if !path.exists() {
+            return Ok(Self::CREATE_SQL.to_string())
+        }
+
+        std::fs::read_to_string(&path).map_err(|e| ClickhouseError::SqlFileReadError(format!("{}: {e}", path.display())))
+    }
+
+    /// `<DATABASE NAME>.<TABLE NAME>.sql`, without the backticks of the table
+    /// name
+    fn override_file_name() -> String {
+        format!("{}.{}.sql", Self::DATABASE_NAME, Self::TABLE_NAME.trim_matches('`'))
+    }
+
     /// name of the database
     fn database_name() -> String {
         Self::DATABASE_NAME.to_string()
@@ -59,3 +261,19 @@ where
         format!("{}.{}", Self::DATABASE_NAME, Self::TABLE_NAME)
     }
 }
+
+/// the `TO` table of a `CREATE MATERIALIZED VIEW` statement
+fn view_target(create_sql: &str) -> Option<String> {
+    static VIEW_TO: OnceLock<Regex> = OnceLock::new();
+
+    let view_to = VIEW_TO.get_or_init(|| {
+        Regex::new(
+            r#"(?is)^(?:\s+|--[^\n]*|/\*.*?\*/)*CREATE\s+MATERIALIZED\s+VIEW\s+(?:IF\s+NOT\s+EXISTS\s+)?[\w.`"]+(?:\s+ON\s+CLUSTER\s+\S+)?\s+TO\s+([\w.`"]+)"#
+        )
+        .unwrap()
+    });
+
+    view_to
+        .captures(create_sql)
+        .map(|captures| captures[1].to_string())
+}
@@ -1,16 +1,17 @@
 use std::{collections::HashSet, pin::Pin};
 
 use eyre::Result;
-use futures::{future::join_all, Future};
+use futures::{future::join_all, Future, Stream};
 use rand::Rng;
 
 use super::ClickhouseTestDBMS;
 use crate::{
-    clickhouse::{client::ClickhouseClient, types::ClickhouseQuery},
+    clickhouse::{client::ClickhouseClient, handle::TableHandle, tables::ClickhouseTable, types::ClickhouseBackend},
     errors::DatabaseError,
+    options::QueryOptions,
     params::BindParameters,
     test_utils::TestDatabase,
-    Database, DatabaseTable
+    Database, DatabaseInsert, DatabaseQuery, DatabaseTable
 };
 
 #[derive(Clone)]
@@ -26,6 +27,14 @@ where
         Self { client }
     }
 
+    /// the [`TableHandle`] of the table, on the test database
+    pub fn table<T>(&self) -> TableHandle<'_, T, D, Self>
+    where
+        T: ClickhouseTable<D> + DatabaseTable<DataType = <T as ClickhouseTable<D>>::ClickhouseDataType>
+    {
+        TableHandle::new(self)
+    }
+
     pub async fn setup(&self, tables: Option<&[D]>) -> Result<(), DatabaseError> {
         self.setup_cleanup(tables, false).await?; // drops all dbs if necessary
         self.setup_cleanup(tables, true).await?; // drops all dbs
@@ -73,39 +82,35 @@ impl<D> Database for ClickhouseTestClient<D>
 where
     D: ClickhouseTestDBMS + 'static
 {
+    type Backend = ClickhouseBackend;
     type DBMS = D;
 
-    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError> {
-        let table = format!("test_{}", Self::DBMS::from_database_table_str(T::NAME).full_name());
-        let mut insert = self.client.client.insert(table)?;
-
-        insert.write(value).await?;
-
-        insert.end().await?;
-
-        Ok(())
+    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        self.insert_many::<T>(std::slice::from_ref(value)).await
     }
 
-    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError> {
-        let table = format!("test_{}", Self::DBMS::from_database_table_str(T::NAME).full_name());
-        let mut insert = self.client.client.insert(table)?;
-
-        for value in values {
-            insert.write(value).await?;
-        }
-
-        insert.end().await?;
-
-        Ok(())
+    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        self.insert_many_with_options::<T>(values, &QueryOptions::default())
+            .await
     }
 
-    async fn query_one<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<Q, DatabaseError> {
+    async fn query_one<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Q, DatabaseError> {
         let query: String = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
 
         self.client.query_one(&query, params).await
     }
 
-    async fn query_one_optional<Q: ClickhouseQuery, P: BindParameters>(
+    async fn query_one_optional<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
         &self,
         query: impl AsRef<str> + Send,
         params: &P
@@ -115,13 +120,31 @@ where
         self.client.query_one_optional(&query, params).await
     }
 
-    async fn query_many<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<Vec<Q>, DatabaseError> {
+    async fn query_many<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<Q>, DatabaseError> {
         let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
 
         self.client.query_many(&query, params).await
     }
 
-    async fn query_raw<Q: ClickhouseQuery, P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<Vec<u8>, DatabaseError> {
+    fn query_stream<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> impl Stream<Item = Result<Q, DatabaseError>> + Send {
+        let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
+
+        self.client.query_stream(query, params)
+    }
+
+    async fn query_raw<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<u8>, DatabaseError> {
         let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
         self.client.query_raw::<Q, P>(&query, params).await
     }
@@ -131,6 +154,82 @@ where
 
         self.client.execute_remote(&query, params).await
     }
+
+    async fn insert_many_with_options<T: DatabaseTable>(&self, values: &[T::DataType], options: &QueryOptions) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<ClickhouseBackend>
+    {
+        let table = format!("test_{}", Self::DBMS::from_database_table_str(T::NAME).full_name());
+
+        self.client
+            .insert_rows_with_options(&table, values, &options.clone().with_table::<T>())
+            .await
+    }
+
+    async fn query_one_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Q, DatabaseError> {
+        let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
+
+        self.client
+            .query_one_with_options(&query, params, options)
+            .await
+    }
+
+    async fn query_one_optional_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Option<Q>, DatabaseError> {
+        let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
+
+        self.client
+            .query_one_optional_with_options(&query, params, options)
+            .await
+    }
+
+    async fn query_many_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Vec<Q>, DatabaseError> {
+        let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
+
+        self.client
+            .query_many_with_options(&query, params, options)
+            .await
+    }
+
+    async fn query_raw_with_options<Q: DatabaseQuery<ClickhouseBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<Vec<u8>, DatabaseError> {
+        let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
+
+        self.client
+            .query_raw_with_options::<Q, P>(&query, params, options)
+            .await
+    }
+
+    async fn execute_remote_with_options<P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        options: &QueryOptions
+    ) -> Result<(), DatabaseError> {
+        let query = <Self as TestDatabase<D>>::modify_query_str(query.as_ref());
+
+        self.client
+            .execute_remote_with_options(&query, params, options)
+            .await
+    }
 }
 
 impl<D> TestDatabase<D> for ClickhouseTestClient<D>
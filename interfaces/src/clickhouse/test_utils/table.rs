@@ -1,7 +1,7 @@
 use super::{ClickhouseTestClient, ClickhouseTestDBMS};
 use crate::{
     clickhouse::{
-        errors::ClickhouseError,
+        migrations::on_cluster,
         tables::{ClickhouseTable, ClickhouseTableKind}
     },
     errors::DatabaseError,
@@ -18,9 +18,21 @@ where
         random_seed: u32
     ) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
         async move {
-            let table_sql_path = Self::FILE_PATH;
-            let mut create_sql = std::fs::read_to_string(table_sql_path).map_err(|e| ClickhouseError::SqlFileReadError(e.to_string()))?;
-            create_sql = Self::replace_test_str(create_sql);
+            let create_sql = on_cluster(&Self::create_sql(&database.client)?, D::CLUSTER);
+
+            Self::create_test_table_from_sql(database, random_seed, create_sql).await
+        }
+    }
+
+    /// FOR TESTING: creates the test table from its `CREATE` statement and the
+    /// associated test tables
+    fn create_test_table_from_sql(
+        database: &ClickhouseTestClient<D>,
+        random_seed: u32,
+        create_sql: String
+    ) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
+        async move {
+            let mut create_sql = Self::replace_test_str(create_sql);
 
             let table_type = Self::TABLE_TYPE;
             if matches!(table_type, ClickhouseTableKind::Distributed) {
@@ -1,16 +1,71 @@
+use std::pin::Pin;
+
 use ::clickhouse::{DbRow, InsertRow};
-use clickhouse::Row;
+use clickhouse::{query::Query, Client, Row};
 use dyn_clone::DynClone;
+use futures::{Future, Stream};
 use serde::{Deserialize, Serialize};
 
-use crate::DatabaseQuery;
+use crate::{errors::DatabaseError, DatabaseBackend, DatabaseInsert, DatabaseQuery};
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, Row)]
 pub struct NoneType();
 
+/// the clickhouse backend, rows are inserted and queried with the
+/// [`clickhouse`] crate
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClickhouseBackend;
+
+impl DatabaseBackend for ClickhouseBackend {
+    type Connection = Client;
+    type Query = Query;
+}
+
 pub trait ClickhouseInsert: Serialize + InsertRow + Send + Sync + 'static + DynClone + Sized {}
 impl<T> ClickhouseInsert for T where T: Serialize + InsertRow + Send + Sync + 'static + DynClone + Sized {}
 
-pub trait ClickhouseQuery: for<'a> Deserialize<'a> + DbRow + Send + Sync {}
-impl<T> ClickhouseQuery for T where T: for<'a> Deserialize<'a> + DbRow + Send + Sync {}
-impl<T> DatabaseQuery for T where T: ClickhouseQuery {}
+pub trait ClickhouseQuery: for<'a> Deserialize<'a> + DbRow + Send + Sync + 'static {}
+impl<T> ClickhouseQuery for T where T: for<'a> Deserialize<'a> + DbRow + Send + Sync + 'static {}
+
+impl<T> DatabaseInsert<ClickhouseBackend> for T
+where
+    T: ClickhouseInsert
+{
+    fn insert_rows<'a>(client: &'a Client, table: &'a str, rows: &'a [Self]) -> Pin<Box<dyn Future<Output = Result<(), DatabaseError>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut insert = client.insert(table.to_string())?;
+
+            for row in rows {
+                insert.write(row).await?;
+            }
+
+            insert.end().await?;
+
+            Ok(())
+        })
+    }
+}
+
+impl<T> DatabaseQuery<ClickhouseBackend> for T
+where
+    T: ClickhouseQuery
+{
+    fn fetch_rows(query: Query) -> Pin<Box<dyn Stream<Item = Result<Self, DatabaseError>> + Send>> {
+        let cursor = query.fetch::<T>();
+
+        Box::pin(futures::stream::unfold(Some(cursor), |cursor| async move {
+            match cursor? {
+                Ok(mut cursor) => match cursor.next().await {
+                    Ok(Some(row)) => Some((Ok(row), Some(Ok(cursor)))),
+                    Ok(None) => None,
+                    Err(e) => Some((Err(e.into()), None))
+                },
+                Err(e) => Some((Err(e.into()), None))
+            }
+        }))
+    }
+
+    fn fetch_raw(query: Query) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, DatabaseError>> + Send>> {
+        Box::pin(async move { Ok(query.fetch_raw::<T>().await?) })
+    }
+}
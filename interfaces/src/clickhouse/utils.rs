@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+
 /// formats a vec into a ? operator in a sql query
 pub fn format_query_array<T: ToString>(vals: &[T], query: &str) -> String {
     let strings = vals.iter().map(|v| v.to_string()).collect::<Vec<_>>();
@@ -10,3 +14,13 @@ pub fn format_query_array<T: ToString>(vals: &[T], query: &str) -> String {
 
     query.replace('?', &final_str)
 }
+
+/// deserializes a duration from milliseconds
+pub(crate) fn deserialize_millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_millis)
+}
+
+/// deserializes an optional duration from milliseconds
+pub(crate) fn deserialize_optional_millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+}
@@ -1,10 +1,42 @@
 use std::fmt::{Debug, Display};
 
-use crate::clickhouse::errors::ClickhouseError;
+#[cfg(feature = "test-utils")]
+use crate::test_utils::mock::errors::MockError;
+use crate::{clickhouse::errors::ClickhouseError, inserts::InsertSummary};
 
 #[derive(Debug)]
 pub enum DatabaseError {
-    ClickhouseError(ClickhouseError)
+    ClickhouseError(ClickhouseError),
+    #[cfg(feature = "sqlite")]
+    SqliteError(crate::sqlite::errors::SqliteError),
+    #[cfg(feature = "test-utils")]
+    MockError(MockError),
+    /// a chunked insert failed after `summary` was already committed
+    PartialInsert {
+        summary: InsertSummary,
+        error:   Box<DatabaseError>
+    }
+}
+
+impl DatabaseError {
+    /// the clickhouse error, looking through partial inserts
+    pub fn clickhouse_error(&self) -> Option<&ClickhouseError> {
+        match self {
+            DatabaseError::ClickhouseError(e) => Some(e),
+            DatabaseError::PartialInsert { error, .. } => error.clickhouse_error(),
+            _ => None
+        }
+    }
+
+    /// code of the exception returned by the clickhouse server
+    pub fn server_code(&self) -> Option<u32> {
+        self.clickhouse_error().and_then(ClickhouseError::code)
+    }
+
+    /// message of the exception returned by the clickhouse server
+    pub fn server_message(&self) -> Option<&str> {
+        self.clickhouse_error().and_then(ClickhouseError::message)
+    }
 }
 
 impl Display for DatabaseError {
@@ -26,3 +58,17 @@ impl From<ClickhouseError> for DatabaseError {
         Self::ClickhouseError(value)
     }
 }
+
+#[cfg(feature = "sqlite")]
+impl From<crate::sqlite::errors::SqliteError> for DatabaseError {
+    fn from(value: crate::sqlite::errors::SqliteError) -> Self {
+        Self::SqliteError(value)
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl From<MockError> for DatabaseError {
+    fn from(value: MockError) -> Self {
+        Self::MockError(value)
+    }
+}
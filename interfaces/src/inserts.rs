@@ -0,0 +1,65 @@
+use std::io;
+
+use serde::Serialize;
+
+/// size at which rows from `insert_stream`/`insert_iter` are split into
+/// separate inserts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertChunkSize {
+    /// number of rows per insert
+    Rows(usize),
+    /// estimated number of bytes per insert
+    Bytes(usize)
+}
+
+impl InsertChunkSize {
+    /// weight the row adds to the current chunk
+    pub fn row_weight<R: Serialize>(&self, row: &R) -> usize {
+        match self {
+            InsertChunkSize::Rows(_) => 1,
+            InsertChunkSize::Bytes(_) => estimated_size(row)
+        }
+    }
+
+    /// max weight of a chunk
+    pub fn limit(&self) -> usize {
+        match self {
+            InsertChunkSize::Rows(rows) => *rows,
+            InsertChunkSize::Bytes(bytes) => *bytes
+        }
+    }
+}
+
+impl Default for InsertChunkSize {
+    fn default() -> Self {
+        InsertChunkSize::Rows(100_000)
+    }
+}
+
+/// rows and inserts committed by `insert_stream`/`insert_iter`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InsertSummary {
+    pub rows:   usize,
+    pub chunks: usize
+}
+
+/// rough size of a row, the length of its json encoding
+pub(crate) fn estimated_size<R: Serialize>(row: &R) -> usize {
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, row)
+        .map(|_| counter.0)
+        .unwrap_or_else(|_| std::mem::size_of::<R>())
+}
+
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
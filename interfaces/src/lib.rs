@@ -1,47 +1,136 @@
 pub mod clickhouse;
 pub mod errors;
+pub mod inserts;
+pub mod options;
 pub mod params;
 pub mod tables;
 
 #[cfg(feature = "alloy-types")]
 pub mod alloy_types;
 
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
 //#[cfg(feature = "test-utils")]
 pub mod test_utils;
 
-use clickhouse::types::ClickhouseQuery;
-pub use db_interfaces_macros::remote_clickhouse_table;
+use std::pin::Pin;
+
+pub use db_interfaces_macros::{remote_clickhouse_table, ClickhouseTable};
+use dyn_clone::DynClone;
 use errors::DatabaseError;
+use futures::{Future, Stream, StreamExt};
+use inserts::{InsertChunkSize, InsertSummary};
+use options::QueryOptions;
 use params::BindParameters;
 use tables::*;
 
 //#[async_trait::async_trait]
 pub trait Database: Sync + Send {
     type DBMS;
+    /// the storage backend, inserted and queried rows implement
+    /// [`DatabaseInsert`]/[`DatabaseQuery`] for it
+    type Backend: DatabaseBackend;
+
+    fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send
+    where
+        T::DataType: DatabaseInsert<Self::Backend>;
+
+    fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send
+    where
+        T::DataType: DatabaseInsert<Self::Backend>;
+
+    /// inserts the rows of the stream, split into inserts of `chunk_size`
+    ///
+    /// the chunks are committed one after the other, so on failure the rows of
+    /// the previous chunks stay inserted and are reported in
+    /// [`DatabaseError::PartialInsert`]. a failure before any chunk was
+    /// committed is returned as is
+    fn insert_stream<T: DatabaseTable, S: Stream<Item = T::DataType> + Send>(
+        &self,
+        values: S,
+        chunk_size: InsertChunkSize
+    ) -> impl std::future::Future<Output = Result<InsertSummary, DatabaseError>> + Send
+    where
+        T::DataType: DatabaseInsert<Self::Backend>
+    {
+        async move {
+            let mut values = std::pin::pin!(values);
+            let mut summary = InsertSummary::default();
+
+            let mut chunk = Vec::new();
+            let mut chunk_weight = 0;
+            loop {
+                let value = values.next().await;
+                let done = value.is_none();
+
+                if let Some(value) = value {
+                    chunk_weight += chunk_size.row_weight(&value);
+                    chunk.push(value);
+                }
+
+                if !chunk.is_empty() && (done || chunk_weight >= chunk_size.limit()) {
+                    self.insert_many::<T>(&chunk).await.map_err(|error| {
+                        if summary.rows == 0 {
+                            error
+                        } else {
+                            DatabaseError::PartialInsert { summary, error: Box::new(error) }
+                        }
+                    })?;
+
+                    summary.rows += chunk.len();
+                    summary.chunks += 1;
+                    chunk.clear();
+                    chunk_weight = 0;
+                }
 
-    fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send;
+                if done {
+                    return Ok(summary)
+                }
+            }
+        }
+    }
 
-    fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send;
+    /// inserts the rows of the iterator, split into inserts of `chunk_size`
+    fn insert_iter<T: DatabaseTable, I>(
+        &self,
+        values: I,
+        chunk_size: InsertChunkSize
+    ) -> impl std::future::Future<Output = Result<InsertSummary, DatabaseError>> + Send
+    where
+        T::DataType: DatabaseInsert<Self::Backend>,
+        I: IntoIterator<Item = T::DataType>,
+        I::IntoIter: Send
+    {
+        self.insert_stream::<T, _>(futures::stream::iter(values), chunk_size)
+    }
 
-    fn query_one<Q: DatabaseQuery, P: BindParameters>(
+    fn query_one<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
         &self,
         query: impl AsRef<str> + Send,
         params: &P
     ) -> impl std::future::Future<Output = Result<Q, DatabaseError>> + Send;
 
-    fn query_one_optional<Q: DatabaseQuery, P: BindParameters>(
+    fn query_one_optional<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
         &self,
         query: impl AsRef<str> + Send,
         params: &P
     ) -> impl std::future::Future<Output = Result<Option<Q>, DatabaseError>> + Send;
 
-    fn query_many<Q: DatabaseQuery, P: BindParameters>(
+    fn query_many<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
         &self,
         query: impl AsRef<str> + Send,
         params: &P
     ) -> impl std::future::Future<Output = Result<Vec<Q>, DatabaseError>> + Send;
 
-    fn query_raw<Q: DatabaseQuery, P: BindParameters>(
+    /// streams the rows of the query instead of collecting them
+    fn query_stream<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> impl futures::Stream<Item = Result<Q, DatabaseError>> + Send;
+
+    fn query_raw<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
         &self,
         query: impl AsRef<str> + Send,
         params: &P
@@ -52,6 +141,115 @@ pub trait Database: Sync + Send {
         query: impl AsRef<str> + Send,
         params: &P
     ) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send;
+
+    /// [`Database::insert_one`] with [`QueryOptions`], ignored unless the
+    /// backend overrides this
+    fn insert_one_with_options<T: DatabaseTable>(
+        &self,
+        value: &T::DataType,
+        options: &QueryOptions
+    ) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send
+    where
+        T::DataType: DatabaseInsert<Self::Backend>
+    {
+        self.insert_many_with_options::<T>(std::slice::from_ref(value), options)
+    }
+
+    /// [`Database::insert_many`] with [`QueryOptions`], ignored unless the
+    /// backend overrides this
+    fn insert_many_with_options<T: DatabaseTable>(
+        &self,
+        values: &[T::DataType],
+        _options: &QueryOptions
+    ) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send
+    where
+        T::DataType: DatabaseInsert<Self::Backend>
+    {
+        self.insert_many::<T>(values)
+    }
+
+    /// [`Database::query_one`] with [`QueryOptions`], ignored unless the
+    /// backend overrides this
+    fn query_one_with_options<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        _options: &QueryOptions
+    ) -> impl std::future::Future<Output = Result<Q, DatabaseError>> + Send {
+        self.query_one(query, params)
+    }
+
+    /// [`Database::query_one_optional`] with [`QueryOptions`], ignored unless
+    /// the backend overrides this
+    fn query_one_optional_with_options<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        _options: &QueryOptions
+    ) -> impl std::future::Future<Output = Result<Option<Q>, DatabaseError>> + Send {
+        self.query_one_optional(query, params)
+    }
+
+    /// [`Database::query_many`] with [`QueryOptions`], ignored unless the
+    /// backend overrides this
+    fn query_many_with_options<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        _options: &QueryOptions
+    ) -> impl std::future::Future<Output = Result<Vec<Q>, DatabaseError>> + Send {
+        self.query_many(query, params)
+    }
+
+    /// [`Database::query_raw`] with [`QueryOptions`], ignored unless the
+    /// backend overrides this
+    fn query_raw_with_options<Q: DatabaseQuery<Self::Backend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        _options: &QueryOptions
+    ) -> impl std::future::Future<Output = Result<Vec<u8>, DatabaseError>> + Send {
+        self.query_raw::<Q, P>(query, params)
+    }
+
+    /// [`Database::execute_remote`] with [`QueryOptions`], ignored unless the
+    /// backend overrides this
+    fn execute_remote_with_options<P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P,
+        _options: &QueryOptions
+    ) -> impl std::future::Future<Output = Result<(), DatabaseError>> + Send {
+        self.execute_remote(query, params)
+    }
+}
+
+/// a storage backend, declaring what its implementations of
+/// [`DatabaseInsert`] and [`DatabaseQuery`] are given to work with
+pub trait DatabaseBackend: Send + Sync + 'static {
+    /// the connection rows are inserted with
+    type Connection: Send + Sync;
+    /// a query with its parameters bound
+    type Query: Send;
+}
+
+/// a row that can be inserted with the backend `B`, the bounds specific to the
+/// backend are on the backend's implementation
+pub trait DatabaseInsert<B: DatabaseBackend>: serde::Serialize + DynClone + Send + Sync + 'static + Sized {
+    /// inserts the rows into the table
+    fn insert_rows<'a>(
+        connection: &'a B::Connection,
+        table: &'a str,
+        rows: &'a [Self]
+    ) -> Pin<Box<dyn Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
 }
 
-pub trait DatabaseQuery: ClickhouseQuery {}
+/// a row that can be queried with the backend `B`, the bounds specific to the
+/// backend are on the backend's implementation
+pub trait DatabaseQuery<B: DatabaseBackend>: for<'de> serde::Deserialize<'de> + Send + Sync + 'static + Sized {
+    /// streams the rows returned by the query
+    fn fetch_rows(query: B::Query) -> Pin<Box<dyn Stream<Item = Result<Self, DatabaseError>> + Send>>;
+
+    /// the raw response of the query
+    fn fetch_raw(query: B::Query) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, DatabaseError>> + Send>>;
+}
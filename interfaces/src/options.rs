@@ -0,0 +1,106 @@
+use std::{collections::BTreeMap, time::Duration};
+
+pub use tokio_util::sync::CancellationToken;
+
+use crate::tables::DatabaseTable;
+
+/// options of a single query, insert or execute, passed to the
+/// `*_with_options` methods of [`Database`](crate::Database)
+///
+/// backends that have no use for an option ignore it
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    /// server settings, e.g. `max_threads = 4`
+    pub settings:     BTreeMap<String, String>,
+    /// id of the query on the server, generated by the server if `None`
+    pub query_id:     Option<String>,
+    /// comment recorded with the query in `system.query_log`
+    pub log_comment:  Option<String>,
+    /// time after which an attempt of the request fails client side, replaces
+    /// the request timeout of the client
+    pub timeout:      Option<Duration>,
+    /// cancels the call when the token is cancelled, the query is killed on
+    /// the server
+    pub cancellation: Option<CancellationToken>,
+    /// the [`DatabaseTable::NAME`] of the table the query is on, the metrics
+    /// of the query are labeled with it (`other` without it)
+    pub table:        Option<&'static str>
+}
+
+impl QueryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets a server setting, replacing it if it was already set
+    pub fn with_setting(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.settings.insert(name.into(), value.to_string());
+        self
+    }
+
+    pub fn with_query_id(mut self, query_id: impl Into<String>) -> Self {
+        self.query_id = Some(query_id.into());
+        self
+    }
+
+    pub fn with_log_comment(mut self, log_comment: impl Into<String>) -> Self {
+        self.log_comment = Some(log_comment.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    pub fn with_table<T: DatabaseTable>(mut self) -> Self {
+        self.table = Some(T::NAME);
+        self
+    }
+
+    /// `max_execution_time`, the server cancels the query after this long. the
+    /// setting is in seconds so the duration is rounded up
+    pub fn with_max_execution_time(self, max_execution_time: Duration) -> Self {
+        let secs = max_execution_time.as_millis().div_ceil(1000).max(1);
+        self.with_setting("max_execution_time", secs)
+    }
+
+    /// `max_memory_usage`, in bytes
+    pub fn with_max_memory_usage(self, bytes: u64) -> Self {
+        self.with_setting("max_memory_usage", bytes)
+    }
+
+    /// `final`, applies `FINAL` to every table of the query
+    pub fn with_final(self, enabled: bool) -> Self {
+        self.with_setting("final", enabled as u8)
+    }
+
+    /// true if no option is set
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty()
+            && self.query_id.is_none()
+            && self.log_comment.is_none()
+            && self.timeout.is_none()
+            && self.cancellation.is_none()
+            && self.table.is_none()
+    }
+
+    /// the query parameters sent to the server, the settings along with the
+    /// `query_id` and `log_comment`
+    pub fn server_params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.settings
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .chain(self.query_id.as_deref().map(|id| ("query_id", id)))
+            .chain(
+                self.log_comment
+                    .as_deref()
+                    .map(|comment| ("log_comment", comment))
+            )
+    }
+}
@@ -4,14 +4,37 @@ use std::fmt::Debug;
 use clickhouse::{query::Query, sql::Bind};
 use serde::Serialize;
 
+/// the values bound to each `?` of a query
+pub type ParamValues = Result<Vec<serde_json::Value>, serde_json::Error>;
+
 pub trait BindParameters: Send + Sync {
     fn bind_query(&self, query: Query) -> Query;
+
+    /// the value bound to each `?` of the query, for backends that bind
+    /// positional values instead of formatting them into the query (sqlite and
+    /// the mock). clickhouse only uses [`BindParameters::bind_query`], the
+    /// default is an error
+    fn param_values(&self) -> ParamValues {
+        Err(serde::ser::Error::custom(format!(
+            "{} only binds clickhouse queries, it has no positional parameter values",
+            std::any::type_name::<Self>()
+        )))
+    }
 }
 
 impl<T: BindParameters + Serialize> BindParameters for &T {
     fn bind_query(&self, query: Query) -> Query {
         query.bind(self)
     }
+
+    fn param_values(&self) -> ParamValues {
+        single_param_value(self)
+    }
+}
+
+/// serializes a query parameter bound to a single `?`
+pub fn single_param_value<T: Serialize + ?Sized>(value: &T) -> ParamValues {
+    Ok(vec![serde_json::to_value(value)?])
 }
 
 #[macro_export]
@@ -26,6 +49,10 @@ macro_rules! impl_bind_parameters {
                 fn bind_query(&self, query: Query) -> Query {
                     query.bind(self)
                 }
+
+                fn param_values(&self) -> $crate::params::ParamValues {
+                    $crate::params::single_param_value(self)
+                }
             }
         )*
     };
@@ -42,6 +69,10 @@ macro_rules! impl_simple_bind_parameters {
                 fn bind_query(&self, query: Query) -> Query {
                     query.bind(self)
                 }
+
+                fn param_values(&self) -> ParamValues {
+                    single_param_value(self)
+                }
             }
         )*
     };
@@ -56,6 +87,10 @@ impl BindParameters for &str {
     fn bind_query(&self, query: Query) -> Query {
         query.bind(self)
     }
+
+    fn param_values(&self) -> ParamValues {
+        single_param_value(self)
+    }
 }
 
 /// single generic bind params
@@ -70,6 +105,10 @@ macro_rules! impl_generic_bind_parameters {
                 fn bind_query(&self, query: Query) -> Query {
                     query.bind(self)
                 }
+
+                fn param_values(&self) -> ParamValues {
+                    single_param_value(self)
+                }
             }
         )*
     };
@@ -91,6 +130,12 @@ macro_rules! impl_tuple_bind_parameters {
                 )*
                 query
             }
+
+            fn param_values(&self) -> ParamValues {
+                #[allow(unused_variables)]
+                let ($($T,)*) = self;
+                Ok(vec![$(serde_json::to_value($T)?),*])
+            }
         }
     };
 }
@@ -0,0 +1,195 @@
+use std::{
+    marker::PhantomData,
+    path::Path,
+    sync::{Arc, Mutex}
+};
+
+use futures::{Stream, TryStreamExt};
+use rusqlite::{params_from_iter, Connection};
+
+use super::{
+    dbms::SqliteDBMS,
+    errors::SqliteError,
+    types::{read_row, to_sql_value, SqliteBackend, SqliteQuery}
+};
+use crate::{errors::DatabaseError, params::BindParameters, Database, DatabaseInsert, DatabaseQuery, DatabaseTable};
+
+/// column names and values of the rows returned by a query
+pub(crate) type FetchedRows = (Vec<String>, Vec<Vec<serde_json::Value>>);
+
+#[derive(Clone)]
+pub struct SqliteClient<D> {
+    pub connection: Arc<Mutex<Connection>>,
+    pub _phantom:   PhantomData<D>
+}
+
+impl<D> SqliteClient<D>
+where
+    D: SqliteDBMS
+{
+    /// opens (or creates) the database file at the path
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DatabaseError> {
+        let connection = Connection::open(path).map_err(SqliteError::from)?;
+        Ok(Self::new(connection))
+    }
+
+    pub fn open_in_memory() -> Result<Self, DatabaseError> {
+        let connection = Connection::open_in_memory().map_err(SqliteError::from)?;
+        Ok(Self::new(connection))
+    }
+
+    pub fn new(connection: Connection) -> Self {
+        Self { connection: Arc::new(Mutex::new(connection)), _phantom: PhantomData }
+    }
+
+    /// creates every table of the DBMS
+    pub async fn create_tables(&self) -> Result<(), DatabaseError> {
+        let create_sql = D::all_tables()
+            .iter()
+            .map(|table| table.create_sql())
+            .collect::<Vec<_>>();
+
+        self.with_connection(move |conn| {
+            for sql in create_sql {
+                conn.execute_batch(sql)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// runs the closure with the connection on the blocking thread pool
+    pub async fn with_connection<R, F>(&self, f: F) -> Result<R, DatabaseError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<R, SqliteError> + Send + 'static
+    {
+        with_connection(self.connection.clone(), f).await
+    }
+
+    /// binds the parameters to the query
+    pub fn query<P: BindParameters>(&self, query: &str, params: &P) -> SqliteQuery {
+        SqliteQuery { connection: self.connection.clone(), sql: query.to_string(), params: params.param_values() }
+    }
+}
+
+impl<D> Database for SqliteClient<D>
+where
+    D: SqliteDBMS
+{
+    type Backend = SqliteBackend;
+    type DBMS = D;
+
+    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<SqliteBackend>
+    {
+        self.insert_many::<T>(std::slice::from_ref(value)).await
+    }
+
+    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<SqliteBackend>
+    {
+        let table = Self::DBMS::from_database_table_str(T::NAME).table_name();
+
+        T::DataType::insert_rows(&self.connection, table, values).await
+    }
+
+    async fn query_one<Q: DatabaseQuery<SqliteBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Q, DatabaseError> {
+        self.query_one_optional(query, params)
+            .await?
+            .ok_or_else(|| SqliteError::RowNotFound.into())
+    }
+
+    async fn query_one_optional<Q: DatabaseQuery<SqliteBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Option<Q>, DatabaseError> {
+        let mut rows = std::pin::pin!(self.query_stream::<Q, P>(query, params));
+
+        rows.try_next().await
+    }
+
+    async fn query_many<Q: DatabaseQuery<SqliteBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<Q>, DatabaseError> {
+        self.query_stream::<Q, P>(query, params).try_collect().await
+    }
+
+    fn query_stream<Q: DatabaseQuery<SqliteBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> impl Stream<Item = Result<Q, DatabaseError>> + Send {
+        Q::fetch_rows(self.query(query.as_ref(), params))
+    }
+
+    async fn query_raw<Q: DatabaseQuery<SqliteBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<u8>, DatabaseError> {
+        Q::fetch_raw(self.query(query.as_ref(), params)).await
+    }
+
+    async fn execute_remote<P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<(), DatabaseError> {
+        let query = query.as_ref().to_string();
+        let params = params.param_values().map_err(SqliteError::from)?;
+
+        self.with_connection(move |conn| {
+            if params.is_empty() {
+                conn.execute_batch(&query)?;
+            } else {
+                conn.execute(&query, params_from_iter(params.into_iter().map(to_sql_value)))?;
+            }
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// runs the closure with the connection on the blocking thread pool
+pub(crate) async fn with_connection<R, F>(connection: Arc<Mutex<Connection>>, f: F) -> Result<R, DatabaseError>
+where
+    R: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<R, SqliteError> + Send + 'static
+{
+    let res = tokio::task::spawn_blocking(move || {
+        let mut conn = connection
+            .lock()
+            .map_err(|e| SqliteError::ConnectionError(e.to_string()))?;
+        f(&mut conn)
+    })
+    .await
+    .map_err(|e| SqliteError::ConnectionError(e.to_string()))??;
+
+    Ok(res)
+}
+
+/// runs the query, collecting every row
+pub(crate) fn fetch_all(conn: &Connection, query: &str, params: Vec<serde_json::Value>) -> Result<FetchedRows, SqliteError> {
+    let mut stmt = conn.prepare(query)?;
+    let columns = stmt
+        .column_names()
+        .into_iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+
+    let mut rows = stmt.query(params_from_iter(params.into_iter().map(to_sql_value)))?;
+
+    let mut values = Vec::new();
+    while let Some(row) = rows.next()? {
+        values.push(read_row(row, columns.len())?);
+    }
+
+    Ok((columns, values))
+}
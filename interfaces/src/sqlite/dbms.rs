@@ -0,0 +1,63 @@
+pub trait SqliteDBMS: Sized + Sync + Send {
+    fn all_tables() -> Vec<Self>;
+
+    fn table_name(&self) -> &'static str;
+
+    /// the `CREATE TABLE` statement of the table
+    fn create_sql(&self) -> &'static str;
+
+    fn from_database_table_str(val: &str) -> Self;
+}
+
+/// creates the enum of all tables in a sqlite database, the inputs are:
+/// 1. enum name for the DBMS
+/// 2. set of tables
+///
+/// Example:
+/// ```ignore
+/// db_interfaces::sqlite_dbms!(ExampleDBMS, [Table0, Table1]);
+/// ```
+#[macro_export]
+macro_rules! sqlite_dbms {
+    ($dbms:ident, [$($table:ident),*]) => {
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+        pub enum $dbms {
+            $(
+                #[allow(non_camel_case_types)]
+                $table
+            ),*
+        }
+
+        impl ::db_interfaces::sqlite::dbms::SqliteDBMS for $dbms {
+            fn all_tables() -> Vec<Self> {
+                vec![$($dbms::$table,)*]
+            }
+
+            fn table_name(&self) -> &'static str {
+                match self {
+                    $($dbms::$table => {
+                        <$table as ::db_interfaces::sqlite::tables::SqliteTable<Self>>::TABLE_NAME
+                    })*
+                }
+            }
+
+            fn create_sql(&self) -> &'static str {
+                match self {
+                    $($dbms::$table => {
+                        <$table as ::db_interfaces::sqlite::tables::SqliteTable<Self>>::CREATE_SQL
+                    })*
+                }
+            }
+
+            fn from_database_table_str(value: &str) -> Self {
+                match value {
+                    $(<$table as ::db_interfaces::tables::DatabaseTable>::NAME => {
+                        $dbms::$table
+                    })*
+                    _ => panic!("From str: {value} is not part of SqliteTables")
+                }
+            }
+        }
+    };
+}
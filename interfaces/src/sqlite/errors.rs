@@ -0,0 +1,27 @@
+use std::fmt::Debug;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SqliteError {
+    #[error("sqlite error: {0}")]
+    SqliteNative(rusqlite::Error),
+    #[error("error (de)serializing sqlite row: {0}")]
+    SerdeError(serde_json::Error),
+    #[error("query returned no rows")]
+    RowNotFound,
+    #[error("error accessing the sqlite connection: {0}")]
+    ConnectionError(String)
+}
+
+impl From<rusqlite::Error> for SqliteError {
+    fn from(value: rusqlite::Error) -> Self {
+        SqliteError::SqliteNative(value)
+    }
+}
+
+impl From<serde_json::Error> for SqliteError {
+    fn from(value: serde_json::Error) -> Self {
+        SqliteError::SerdeError(value)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod client;
+pub mod dbms;
+pub mod errors;
+pub mod tables;
+pub mod types;
@@ -0,0 +1,36 @@
+use super::dbms::SqliteDBMS;
+use crate::tables::DatabaseTable;
+
+/// trait for tables of a sqlite database
+pub trait SqliteTable<D>: DatabaseTable
+where
+    D: SqliteDBMS
+{
+    const TABLE_NAME: &'static str;
+    const CREATE_SQL: &'static str;
+    const TABLE_ENUM: D;
+}
+
+/// creates a sqlite table, the inputs are:
+/// 1. enum name of the DBMS
+/// 2. name of the table struct
+/// 3. the type that is used when inserting into the table
+/// 4. name of the table in sqlite
+/// 5. the `CREATE TABLE` statement
+///
+/// Example:
+/// ```ignore
+/// db_interfaces::sqlite_table!(ExampleDBMS, Table0, Table0Row, "table0", "CREATE TABLE IF NOT EXISTS table0 (id INTEGER)");
+/// ```
+#[macro_export]
+macro_rules! sqlite_table {
+    ($dbms:ident, $table_name:ident, $data_type:ty, $sqlite_name:expr, $create_sql:expr) => {
+        ::db_interfaces::database_table!($table_name, $data_type);
+
+        impl ::db_interfaces::sqlite::tables::SqliteTable<$dbms> for $table_name {
+            const CREATE_SQL: &'static str = $create_sql;
+            const TABLE_ENUM: $dbms = $dbms::$table_name;
+            const TABLE_NAME: &'static str = $sqlite_name;
+        }
+    };
+}
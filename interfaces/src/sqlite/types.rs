@@ -0,0 +1,208 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex}
+};
+
+use dyn_clone::DynClone;
+use futures::{Future, Stream, StreamExt, TryStreamExt};
+use rusqlite::{
+    params_from_iter,
+    types::{Value, ValueRef},
+    Connection
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Number};
+
+use super::{
+    client::{fetch_all, with_connection, FetchedRows},
+    errors::SqliteError
+};
+use crate::{errors::DatabaseError, params::ParamValues, DatabaseBackend, DatabaseInsert, DatabaseQuery};
+
+/// the sqlite backend, any row that can be (de)serialized can be inserted and
+/// queried
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqliteBackend;
+
+impl DatabaseBackend for SqliteBackend {
+    type Connection = Arc<Mutex<Connection>>;
+    type Query = SqliteQuery;
+}
+
+/// a query with its bound parameters, along with the connection it runs on
+pub struct SqliteQuery {
+    pub connection: Arc<Mutex<Connection>>,
+    pub sql:        String,
+    pub params:     ParamValues
+}
+
+impl<T> DatabaseInsert<SqliteBackend> for T
+where
+    T: Serialize + DynClone + Send + Sync + 'static
+{
+    fn insert_rows<'a>(
+        connection: &'a Arc<Mutex<Connection>>,
+        table: &'a str,
+        rows: &'a [Self]
+    ) -> Pin<Box<dyn Future<Output = Result<(), DatabaseError>> + Send + 'a>> {
+        Box::pin(async move {
+            let table = table.to_string();
+            let rows = rows
+                .iter()
+                .map(SqliteRow::from_serialize)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            with_connection(connection.clone(), move |conn| {
+                let tx = conn.transaction()?;
+                for row in rows {
+                    tx.prepare_cached(&row.insert_statement(&table))?
+                        .execute(params_from_iter(row.values))?;
+                }
+                tx.commit()?;
+
+                Ok(())
+            })
+            .await
+        })
+    }
+}
+
+impl<T> DatabaseQuery<SqliteBackend> for T
+where
+    T: DeserializeOwned + Send + Sync + 'static
+{
+    /// the rows are collected while the connection is locked, then streamed, so
+    /// the client can be used while the stream is consumed
+    fn fetch_rows(query: SqliteQuery) -> Pin<Box<dyn Stream<Item = Result<Self, DatabaseError>> + Send>> {
+        Box::pin(
+            futures::stream::once(fetch(query))
+                .map_ok(|(columns, rows)| futures::stream::iter(rows).map(move |values| Ok(deserialize_row::<T>(&columns, values)?)))
+                .try_flatten()
+        )
+    }
+
+    /// the rows encoded as a json array of objects
+    fn fetch_raw(query: SqliteQuery) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, DatabaseError>> + Send>> {
+        Box::pin(async move {
+            let (columns, rows) = fetch(query).await?;
+
+            let rows = rows
+                .into_iter()
+                .map(|row| columns.iter().cloned().zip(row).collect::<Map<_, _>>())
+                .collect::<Vec<_>>();
+
+            Ok(serde_json::to_vec(&rows).map_err(SqliteError::from)?)
+        })
+    }
+}
+
+/// runs the query, collecting every row
+async fn fetch(query: SqliteQuery) -> Result<FetchedRows, DatabaseError> {
+    let params = query.params.map_err(SqliteError::from)?;
+    let sql = query.sql;
+
+    with_connection(query.connection, move |conn| fetch_all(conn, &sql, params)).await
+}
+
+/// a row serialized into the values of its columns
+#[derive(Debug, Clone)]
+pub struct SqliteRow {
+    /// the column names, if the row was serialized from a struct or map
+    pub columns: Option<Vec<String>>,
+    pub values:  Vec<Value>
+}
+
+impl SqliteRow {
+    pub fn from_serialize<T: Serialize>(row: &T) -> Result<Self, SqliteError> {
+        let this = match serde_json::to_value(row)? {
+            serde_json::Value::Object(map) => {
+                let (columns, values): (Vec<_>, Vec<_>) = map.into_iter().map(|(k, v)| (k, to_sql_value(v))).unzip();
+                Self { columns: Some(columns), values }
+            }
+            serde_json::Value::Array(values) => Self { columns: None, values: values.into_iter().map(to_sql_value).collect() },
+            value => Self { columns: None, values: vec![to_sql_value(value)] }
+        };
+
+        Ok(this)
+    }
+
+    /// `INSERT INTO` statement for the row, with a `?` for each value
+    pub fn insert_statement(&self, table: &str) -> String {
+        let placeholders = vec!["?"; self.values.len()].join(", ");
+
+        match &self.columns {
+            Some(columns) => {
+                let columns = columns
+                    .iter()
+                    .map(|c| quote_identifier(c))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("INSERT INTO {table} ({columns}) VALUES ({placeholders})")
+            }
+            None => format!("INSERT INTO {table} VALUES ({placeholders})")
+        }
+    }
+}
+
+/// reads the values of the first `columns` columns of a row
+pub fn read_row(row: &rusqlite::Row<'_>, columns: usize) -> Result<Vec<serde_json::Value>, SqliteError> {
+    (0..columns)
+        .map(|i| Ok::<_, SqliteError>(from_sql_value(row.get_ref(i)?)))
+        .collect()
+}
+
+/// deserializes a row, trying (in order) a single column value, an object of
+/// the columns and a sequence of the values
+pub fn deserialize_row<Q: DeserializeOwned>(columns: &[String], values: Vec<serde_json::Value>) -> Result<Q, SqliteError> {
+    if let [value] = values.as_slice() {
+        if let Ok(row) = serde_json::from_value(value.clone()) {
+            return Ok(row)
+        }
+    }
+
+    let object = columns
+        .iter()
+        .cloned()
+        .zip(values.iter().cloned())
+        .collect::<Map<_, _>>();
+
+    serde_json::from_value(serde_json::Value::Object(object))
+        .or_else(|e| serde_json::from_value(serde_json::Value::Array(values)).map_err(|_| e))
+        .map_err(SqliteError::from)
+}
+
+pub fn to_sql_value(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(b as i64),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::Integer(i)
+            } else if n.is_f64() {
+                Value::Real(n.as_f64().unwrap_or_default())
+            } else {
+                // u64 above i64::MAX
+                Value::Text(n.to_string())
+            }
+        }
+        serde_json::Value::String(s) => Value::Text(s),
+        value => Value::Text(value.to_string())
+    }
+}
+
+pub fn from_sql_value(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => Number::from_f64(f)
+            .map(serde_json::Value::Number)
+            .unwrap_or_default(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => b.to_vec().into()
+    }
+}
+
+/// double quotes an identifier, escaping any quotes in it
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
@@ -1,8 +1,8 @@
-use crate::clickhouse::types::ClickhouseInsert;
-
 pub trait DatabaseTable: Default + Send + Sync {
     const NAME: &'static str;
-    type DataType: ClickhouseInsert;
+    /// rows of the table, the bounds needed to insert them are set by each
+    /// backend through [`DatabaseInsert`](crate::DatabaseInsert)
+    type DataType: Send + Sync + 'static;
 }
 
 #[macro_export]
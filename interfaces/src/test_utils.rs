@@ -1,3 +1,6 @@
+#[cfg(feature = "test-utils")]
+pub mod mock;
+
 use std::pin::Pin;
 
 use futures::Future;
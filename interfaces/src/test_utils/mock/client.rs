@@ -0,0 +1,183 @@
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex}
+};
+
+use futures::{future::Either, Stream, TryStreamExt};
+use serde::Serialize;
+
+use super::{
+    errors::MockError,
+    types::{lock_state, MockBackend, MockQuery, MockResponse, MockState, QueryMatcher, RecordedQuery}
+};
+use crate::{errors::DatabaseError, params::BindParameters, Database, DatabaseInsert, DatabaseQuery, DatabaseTable};
+
+/// in-memory [`Database`] for unit tests that don't have a server to run
+/// against
+///
+/// inserts are recorded per table (keyed by [`DatabaseTable::NAME`]), queries
+/// and executes are recorded with their bound params, and queries are answered
+/// with the responses registered with [`MockDatabase::on_query`]
+pub struct MockDatabase<D> {
+    pub state:    Arc<Mutex<MockState>>,
+    pub _phantom: PhantomData<D>
+}
+
+impl<D> Clone for MockDatabase<D> {
+    fn clone(&self) -> Self {
+        Self { state: self.state.clone(), _phantom: PhantomData }
+    }
+}
+
+impl<D> Default for MockDatabase<D> {
+    fn default() -> Self {
+        Self { state: Default::default(), _phantom: PhantomData }
+    }
+}
+
+impl<D> MockDatabase<D> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// answers the queries that match with the rows, the first registered
+    /// matcher that matches a query is used
+    ///
+    /// ```ignore
+    /// mock.on_query("SELECT * FROM db.table0", &rows)?;
+    /// mock.on_query(QueryMatcher::regex(r"FROM db\.table0 WHERE id = ")?, &rows[..1])?;
+    /// ```
+    pub fn on_query<R: Serialize>(&self, matcher: impl Into<QueryMatcher>, rows: &[R]) -> Result<(), DatabaseError> {
+        self.respond(matcher, MockResponse::rows(rows)?);
+        Ok(())
+    }
+
+    /// answers the queries that match with the raw bytes
+    pub fn on_query_raw(&self, matcher: impl Into<QueryMatcher>, raw: impl Into<Vec<u8>>) {
+        self.respond(matcher, MockResponse::Raw(raw.into()))
+    }
+
+    pub fn respond(&self, matcher: impl Into<QueryMatcher>, response: MockResponse) {
+        lock_state(&self.state)
+            .responses
+            .push((matcher.into(), response));
+    }
+
+    /// rows inserted into the table, in the order they were inserted
+    pub fn inserted<T: DatabaseTable>(&self) -> Vec<T::DataType>
+    where
+        T::DataType: DatabaseInsert<MockBackend>
+    {
+        lock_state(&self.state)
+            .inserts
+            .get(T::NAME)
+            .and_then(|rows| rows.downcast_ref::<Vec<T::DataType>>())
+            .map(|rows| rows.iter().map(dyn_clone::clone).collect())
+            .unwrap_or_default()
+    }
+
+    /// every query run, in order
+    pub fn queries(&self) -> Vec<RecordedQuery> {
+        lock_state(&self.state).queries.clone()
+    }
+
+    /// every statement run with `execute_remote`, in order
+    pub fn executed(&self) -> Vec<RecordedQuery> {
+        lock_state(&self.state).executed.clone()
+    }
+
+    /// clears the recorded inserts, queries and executes, keeping the
+    /// registered responses
+    pub fn clear(&self) {
+        let mut state = lock_state(&self.state);
+        state.inserts.clear();
+        state.queries.clear();
+        state.executed.clear();
+    }
+
+    /// records the query and matches it to its response
+    fn query<P: BindParameters>(&self, query: &str, params: &P) -> Result<MockQuery, DatabaseError> {
+        let recorded = RecordedQuery { sql: query.to_string(), params: params.param_values().map_err(MockError::from)? };
+
+        let mut state = lock_state(&self.state);
+        state.queries.push(recorded);
+
+        Ok(MockQuery { sql: query.to_string(), response: state.response(query) })
+    }
+}
+
+impl<D> Database for MockDatabase<D>
+where
+    D: Send + Sync
+{
+    type Backend = MockBackend;
+    type DBMS = D;
+
+    async fn insert_one<T: DatabaseTable>(&self, value: &T::DataType) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<MockBackend>
+    {
+        self.insert_many::<T>(std::slice::from_ref(value)).await
+    }
+
+    async fn insert_many<T: DatabaseTable>(&self, values: &[T::DataType]) -> Result<(), DatabaseError>
+    where
+        T::DataType: DatabaseInsert<MockBackend>
+    {
+        T::DataType::insert_rows(&self.state, T::NAME, values).await
+    }
+
+    async fn query_one<Q: DatabaseQuery<MockBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Q, DatabaseError> {
+        self.query_one_optional(query, params)
+            .await?
+            .ok_or_else(|| MockError::RowNotFound.into())
+    }
+
+    async fn query_one_optional<Q: DatabaseQuery<MockBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Option<Q>, DatabaseError> {
+        let mut rows = std::pin::pin!(self.query_stream::<Q, P>(query, params));
+
+        rows.try_next().await
+    }
+
+    async fn query_many<Q: DatabaseQuery<MockBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<Q>, DatabaseError> {
+        self.query_stream::<Q, P>(query, params).try_collect().await
+    }
+
+    fn query_stream<Q: DatabaseQuery<MockBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> impl Stream<Item = Result<Q, DatabaseError>> + Send {
+        match self.query(query.as_ref(), params) {
+            Ok(query) => Either::Left(Q::fetch_rows(query)),
+            Err(e) => Either::Right(futures::stream::once(async { Err(e) }))
+        }
+    }
+
+    async fn query_raw<Q: DatabaseQuery<MockBackend>, P: BindParameters>(
+        &self,
+        query: impl AsRef<str> + Send,
+        params: &P
+    ) -> Result<Vec<u8>, DatabaseError> {
+        Q::fetch_raw(self.query(query.as_ref(), params)?).await
+    }
+
+    async fn execute_remote<P: BindParameters>(&self, query: impl AsRef<str> + Send, params: &P) -> Result<(), DatabaseError> {
+        let recorded = RecordedQuery { sql: query.as_ref().to_string(), params: params.param_values().map_err(MockError::from)? };
+        lock_state(&self.state).executed.push(recorded);
+
+        Ok(())
+    }
+}
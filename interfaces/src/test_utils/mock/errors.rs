@@ -0,0 +1,29 @@
+use std::fmt::Debug;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MockError {
+    #[error("no response registered for query: {0}")]
+    NoResponse(String),
+    #[error("query returned no rows")]
+    RowNotFound,
+    #[error("rows of a different type were already inserted into {0}")]
+    InsertTypeMismatch(String),
+    #[error("error (de)serializing mock row: {0}")]
+    SerdeError(serde_json::Error),
+    #[error("invalid query regex: {0}")]
+    RegexError(regex::Error)
+}
+
+impl From<serde_json::Error> for MockError {
+    fn from(value: serde_json::Error) -> Self {
+        MockError::SerdeError(value)
+    }
+}
+
+impl From<regex::Error> for MockError {
+    fn from(value: regex::Error) -> Self {
+        MockError::RegexError(value)
+    }
+}
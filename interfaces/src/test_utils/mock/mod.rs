@@ -0,0 +1,3 @@
+pub mod client;
+pub mod errors;
+pub mod types;
@@ -0,0 +1,200 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard}
+};
+
+use dyn_clone::DynClone;
+use futures::{Future, Stream};
+use regex::Regex;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::errors::MockError;
+use crate::{errors::DatabaseError, DatabaseBackend, DatabaseInsert, DatabaseQuery};
+
+/// the in-memory backend of [`MockDatabase`](super::client::MockDatabase),
+/// inserted rows are kept as is and queried rows are deserialized from the
+/// registered responses
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockBackend;
+
+impl DatabaseBackend for MockBackend {
+    type Connection = Arc<Mutex<MockState>>;
+    type Query = MockQuery;
+}
+
+/// everything recorded by the mock, along with the registered responses
+#[derive(Default)]
+pub struct MockState {
+    /// the inserted rows of each table, a `Vec` of the table's data type
+    pub(crate) inserts:   HashMap<String, Box<dyn Any + Send + Sync>>,
+    pub(crate) queries:   Vec<RecordedQuery>,
+    pub(crate) executed:  Vec<RecordedQuery>,
+    pub(crate) responses: Vec<(QueryMatcher, MockResponse)>
+}
+
+impl MockState {
+    /// the response of the first registered matcher that matches the query
+    pub(crate) fn response(&self, sql: &str) -> Option<MockResponse> {
+        self.responses
+            .iter()
+            .find(|(matcher, _)| matcher.matches(sql))
+            .map(|(_, response)| response.clone())
+    }
+}
+
+/// locks the state, a test that panicked while holding the lock doesn't
+/// poison the mock for the others
+pub(crate) fn lock_state(state: &Mutex<MockState>) -> MutexGuard<'_, MockState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// a query and the response it was matched to
+pub struct MockQuery {
+    pub sql:      String,
+    pub response: Option<MockResponse>
+}
+
+/// a query or execute statement, with the parameters bound to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedQuery {
+    pub sql:    String,
+    pub params: Vec<serde_json::Value>
+}
+
+/// canned response returned for the queries that match
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockResponse {
+    /// rows, each deserialized into the queried type
+    Rows(Vec<serde_json::Value>),
+    /// the raw bytes returned by `query_raw`, row queries parse them as a json
+    /// array of rows
+    Raw(Vec<u8>)
+}
+
+impl MockResponse {
+    pub fn rows<R: Serialize>(rows: &[R]) -> Result<Self, DatabaseError> {
+        let rows = rows
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(MockError::from)?;
+
+        Ok(MockResponse::Rows(rows))
+    }
+
+    fn into_rows(self) -> Result<Vec<serde_json::Value>, MockError> {
+        match self {
+            MockResponse::Rows(rows) => Ok(rows),
+            MockResponse::Raw(raw) => Ok(serde_json::from_slice(&raw)?)
+        }
+    }
+
+    fn into_raw(self) -> Result<Vec<u8>, MockError> {
+        match self {
+            MockResponse::Rows(rows) => Ok(serde_json::to_vec(&rows)?),
+            MockResponse::Raw(raw) => Ok(raw)
+        }
+    }
+}
+
+/// matches the sql of a query to a registered response
+#[derive(Debug, Clone)]
+pub enum QueryMatcher {
+    /// the query, ignoring leading and trailing whitespace
+    Exact(String),
+    Regex(Regex)
+}
+
+impl QueryMatcher {
+    pub fn exact(sql: impl Into<String>) -> Self {
+        QueryMatcher::Exact(sql.into())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, DatabaseError> {
+        Ok(QueryMatcher::Regex(Regex::new(pattern).map_err(MockError::from)?))
+    }
+
+    pub fn matches(&self, sql: &str) -> bool {
+        match self {
+            QueryMatcher::Exact(exact) => exact.trim() == sql.trim(),
+            QueryMatcher::Regex(regex) => regex.is_match(sql)
+        }
+    }
+}
+
+impl From<&str> for QueryMatcher {
+    fn from(value: &str) -> Self {
+        QueryMatcher::exact(value)
+    }
+}
+
+impl From<String> for QueryMatcher {
+    fn from(value: String) -> Self {
+        QueryMatcher::exact(value)
+    }
+}
+
+impl From<Regex> for QueryMatcher {
+    fn from(value: Regex) -> Self {
+        QueryMatcher::Regex(value)
+    }
+}
+
+impl<T> DatabaseInsert<MockBackend> for T
+where
+    T: Serialize + DynClone + Send + Sync + 'static
+{
+    fn insert_rows<'a>(
+        state: &'a Arc<Mutex<MockState>>,
+        table: &'a str,
+        rows: &'a [Self]
+    ) -> Pin<Box<dyn Future<Output = Result<(), DatabaseError>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut state = lock_state(state);
+
+            let inserted = state
+                .inserts
+                .entry(table.to_string())
+                .or_insert_with(|| Box::new(Vec::<T>::new()))
+                .downcast_mut::<Vec<T>>()
+                .ok_or_else(|| MockError::InsertTypeMismatch(table.to_string()))?;
+
+            inserted.extend(rows.iter().map(dyn_clone::clone));
+
+            Ok(())
+        })
+    }
+}
+
+impl<T> DatabaseQuery<MockBackend> for T
+where
+    T: DeserializeOwned + Send + Sync + 'static
+{
+    fn fetch_rows(query: MockQuery) -> Pin<Box<dyn Stream<Item = Result<Self, DatabaseError>> + Send>> {
+        let rows = query
+            .response
+            .ok_or(MockError::NoResponse(query.sql))
+            .and_then(MockResponse::into_rows);
+
+        let rows = match rows {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|row| -> Result<T, DatabaseError> { Ok(serde_json::from_value(row).map_err(MockError::from)?) })
+                .collect::<Vec<_>>(),
+            Err(e) => vec![Err(e.into())]
+        };
+
+        Box::pin(futures::stream::iter(rows))
+    }
+
+    fn fetch_raw(query: MockQuery) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, DatabaseError>> + Send>> {
+        let raw = query
+            .response
+            .ok_or(MockError::NoResponse(query.sql))
+            .and_then(MockResponse::into_raw);
+
+        Box::pin(async move { Ok(raw?) })
+    }
+}
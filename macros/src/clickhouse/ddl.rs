@@ -0,0 +1,257 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static CREATE_TABLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)^\s*CREATE\s+(?:OR\s+REPLACE\s+)?TABLE\s+([^(]*?)\(").unwrap());
+static ENGINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bENGINE\s*=\s*([A-Za-z_]\w*)").unwrap());
+static CLAUSE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(ORDER\s+BY|PARTITION\s+BY|PRIMARY\s+KEY|SAMPLE\s+BY|TTL|SETTINGS|COMMENT|POPULATE|AS)\b").unwrap());
+
+/// the `ENGINE` clause of a `CREATE` statement and the table clauses after it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct EngineDef {
+    pub(crate) name:         String,
+    /// the arguments of the engine, unquoted
+    pub(crate) args:         Vec<String>,
+    pub(crate) order_by:     Option<String>,
+    pub(crate) partition_by: Option<String>,
+    pub(crate) primary_key:  Option<String>,
+    pub(crate) ttl:          Option<String>
+}
+
+impl EngineDef {
+    /// the `ENGINE = ...` of the first statement of the sql, `None` if it has
+    /// none (views, `AS remote(...)`, ...)
+    pub(crate) fn parse(sql: &str) -> Option<Self> {
+        let masked = first_statement(sql);
+        let engine = ENGINE.captures(&masked)?;
+        let (name, end) = engine
+            .get(1)
+            .map(|name| (name.as_str().to_string(), name.end()))?;
+
+        let mut this = Self { name, ..Default::default() };
+
+        // the masked statement keeps the offsets of the sql, the content of the
+        // parentheses is read from the sql itself
+        let mut rest = end;
+        let args_start = end + (masked[end..].len() - masked[end..].trim_start().len());
+        if masked[args_start..].starts_with('(') {
+            let args_len = closing_paren(&sql[args_start + 1..])?;
+            let args = &sql[args_start + 1..args_start + 1 + args_len];
+            this.args = split_top_level(args, b',')
+                .into_iter()
+                .map(|arg| unquote(arg.trim()).to_string())
+                .filter(|arg| !arg.is_empty())
+                .collect();
+            rest = args_start + args_len + 2;
+        }
+
+        let clauses = CLAUSE.captures_iter(&masked[rest..]).collect::<Vec<_>>();
+        for (i, clause) in clauses.iter().enumerate() {
+            let keyword = clause.get(1)?;
+            let value_end = clauses
+                .get(i + 1)
+                .and_then(|next| next.get(1))
+                .map_or(masked.len() - rest, |next| next.start());
+            let value = Some(
+                sql[rest + keyword.end()..rest + value_end]
+                    .trim()
+                    .to_string()
+            );
+
+            let keyword = keyword
+                .as_str()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_uppercase();
+            match keyword.as_str() {
+                "ORDER BY" => this.order_by = value,
+                "PARTITION BY" => this.partition_by = value,
+                "PRIMARY KEY" => this.primary_key = value,
+                "TTL" => this.ttl = value,
+                // the select of a materialized view
+                "POPULATE" | "AS" => break,
+                _ => ()
+            }
+        }
+
+        Some(this)
+    }
+}
+
+/// the value of a quoted string or identifier
+fn unquote(value: &str) -> &str {
+    ['\'', '`', '"']
+        .into_iter()
+        .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(value)
+}
+
+/// a column of the column list of a `CREATE TABLE` statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ColumnDef {
+    pub(crate) name:     String,
+    /// `MATERIALIZED` and `ALIAS` columns are computed by the server, the rows
+    /// don't have them
+    pub(crate) computed: bool
+}
+
+/// the columns of a `CREATE TABLE` statement, `None` if it doesn't list its
+/// columns (`AS other_table`, views, ...)
+pub(crate) fn parse_columns(create_sql: &str) -> Option<Vec<ColumnDef>> {
+    let captures = CREATE_TABLE.captures(create_sql)?;
+
+    // `CREATE TABLE t AS other ENGINE = ...(`
+    let name = captures.get(1).map_or("", |name| name.as_str());
+    if name
+        .split_whitespace()
+        .any(|word| word.eq_ignore_ascii_case("AS") || word.eq_ignore_ascii_case("ENGINE"))
+    {
+        return None
+    }
+
+    let body = &create_sql[captures.get(0)?.end()..];
+    let body = &body[..closing_paren(body)?];
+
+    Some(
+        split_top_level(body, b',')
+            .into_iter()
+            .filter_map(parse_column)
+            .collect()
+    )
+}
+
+/// `None` for indexes, projections and constraints
+fn parse_column(definition: &str) -> Option<ColumnDef> {
+    let definition = definition.trim();
+    let first_word = definition.split_whitespace().next()?;
+    if ["INDEX", "PROJECTION", "CONSTRAINT"]
+        .iter()
+        .any(|keyword| first_word.eq_ignore_ascii_case(keyword))
+    {
+        return None
+    }
+
+    let (name, rest) = match definition.strip_prefix('`') {
+        Some(quoted) => quoted.split_once('`')?,
+        None => definition.split_once(char::is_whitespace)?
+    };
+
+    let rest = rest.replace(char::is_whitespace, " ");
+    let computed = split_top_level(&rest, b' ')
+        .into_iter()
+        .any(|word| word.eq_ignore_ascii_case("MATERIALIZED") || word.eq_ignore_ascii_case("ALIAS"));
+
+    Some(ColumnDef { name: name.to_string(), computed })
+}
+
+/// the first statement of the sql with its comments, quoted strings and the
+/// content of its parentheses blanked out, so only its top level clauses can be
+/// matched
+pub(crate) fn first_statement(sql: &str) -> String {
+    let bytes = sql.as_bytes();
+    let mut masked = bytes.to_vec();
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 1;
+            }
+            quote @ (b'\'' | b'"' | b'`') => i = skip_quoted(bytes, i, quote),
+            b'(' => {
+                depth += 1;
+                i += 1;
+                continue
+            }
+            b')' => {
+                depth -= 1;
+                i += 1;
+                continue
+            }
+            b';' if depth == 0 => {
+                masked.truncate(i);
+                break
+            }
+            _ if depth == 0 => {
+                i += 1;
+                continue
+            }
+            _ => ()
+        }
+
+        let end = (i + 1).min(bytes.len());
+        masked[start..end].fill(b' ');
+        i = end;
+    }
+
+    String::from_utf8_lossy(&masked).into_owned()
+}
+
+/// index of the `)` closing the list the string starts in
+pub(crate) fn closing_paren(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' if depth == 0 => return Some(i),
+            b')' => depth -= 1,
+            quote @ (b'\'' | b'"' | b'`') => i = skip_quoted(bytes, i, quote),
+            _ => ()
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// splits on the separators that aren't in parentheses or quotes
+pub(crate) fn split_top_level(s: &str, separator: u8) -> Vec<&str> {
+    let bytes = s.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            quote @ (b'\'' | b'"' | b'`') => i = skip_quoted(bytes, i, quote),
+            c if c == separator && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => ()
+        }
+        i += 1;
+    }
+    parts.push(&s[start.min(s.len())..]);
+
+    parts
+}
+
+/// index of the quote closing the one at `start`
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() && bytes[i] != quote {
+        // skips escaped chars
+        i += if bytes[i] == b'\\' { 2 } else { 1 };
+    }
+
+    i
+}
@@ -0,0 +1,395 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parenthesized, spanned::Spanned, token, Data, DeriveInput, Expr, Fields, GenericArgument, Ident, LitStr, PathArguments, Token, Type};
+
+use super::{ddl::EngineDef, table::engine_consts, types::ClickhouseTableKind};
+
+pub(crate) fn derive_clickhouse_table(token_stream: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = syn::parse2(token_stream)?;
+    let table = TableAttrs::parse(&input)?;
+    let columns = columns(&input)?;
+
+    let create_sql = table.create_sql(&columns);
+    let engine = EngineDef::parse(&create_sql);
+    if engine
+        .as_ref()
+        .is_some_and(|engine| engine.name.ends_with("MergeTree"))
+        && table.order_by.is_none()
+    {
+        return Err(syn::Error::new(table.engine.span(), "MergeTree engines need a `#[ch(order_by = \"...\")]` attribute"))
+    }
+
+    let table_type: TokenStream = ClickhouseTableKind::get_table_type(&create_sql)
+        .map_err(|e| syn::Error::new(table.engine.span(), e))?
+        .into();
+    let engine = engine
+        .map(|engine| engine_consts(&engine))
+        .unwrap_or_default();
+
+    let ident = &input.ident;
+    let TableAttrs { dbms, database, table_name, child_tables, .. } = &table;
+
+    let val = quote! {
+        impl ::db_interfaces::clickhouse::tables::ClickhouseTable<#dbms> for #ident {
+            const DATABASE_NAME: &'static str = #database;
+            const TABLE_NAME: &'static str = #table_name;
+            const FILE_PATH: &'static str = "";
+            const CREATE_SQL: &'static str = #create_sql;
+            const CHILD_TABLES: &'static [#dbms] = &[#(#dbms::#child_tables),*];
+            const TABLE_TYPE: ::db_interfaces::clickhouse::tables::ClickhouseTableKind = #table_type;
+            const TABLE_ENUM: #dbms = #dbms::#ident;
+            type ClickhouseDataType = #ident;
+
+            #engine
+        }
+
+        impl ::db_interfaces::tables::DatabaseTable for #ident {
+            type DataType = #ident;
+
+            const NAME: &'static str = stringify!(#ident);
+        }
+    };
+
+    #[cfg(feature = "test-utils")]
+    let val_test = quote! {
+        impl ::db_interfaces::clickhouse::test_utils::ClickhouseTestTable<#dbms> for #ident {}
+    };
+    #[cfg(feature = "test-utils")]
+    return Ok(quote!(#val #val_test));
+
+    #[cfg(not(feature = "test-utils"))]
+    return Ok(val);
+}
+
+/// the `#[ch(...)]` attributes of the struct
+struct TableAttrs {
+    dbms:         Ident,
+    database:     LitStr,
+    table_name:   LitStr,
+    engine:       LitStr,
+    order_by:     Option<LitStr>,
+    partition_by: Option<LitStr>,
+    primary_key:  Option<LitStr>,
+    ttl:          Option<LitStr>,
+    child_tables: Vec<Ident>
+}
+
+impl TableAttrs {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let (mut dbms, mut database, mut table_name, mut engine) = (None, None, None, None);
+        let (mut order_by, mut partition_by, mut primary_key, mut ttl) = (None, None, None, None);
+        let mut child_tables = Vec::new();
+
+        for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("ch")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("dbms") {
+                    dbms = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("database") {
+                    database = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("table") {
+                    table_name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("engine") {
+                    engine = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("order_by") {
+                    order_by = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("partition_by") {
+                    partition_by = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("primary_key") {
+                    primary_key = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("ttl") {
+                    ttl = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("child_tables") {
+                    meta.parse_nested_meta(|child| {
+                        child_tables.push(child.path.require_ident()?.clone());
+                        Ok(())
+                    })?;
+                } else {
+                    return Err(meta.error("unknown table attribute"))
+                }
+
+                Ok(())
+            })?;
+        }
+
+        let missing = |name: &str| syn::Error::new(Span::call_site(), format!("missing `#[ch({name} = ...)]` attribute"));
+
+        Ok(Self {
+            dbms: dbms.ok_or_else(|| missing("dbms"))?,
+            database: database.ok_or_else(|| missing("database"))?,
+            table_name: table_name.unwrap_or_else(|| LitStr::new(&snake_case(&input.ident.to_string()), input.ident.span())),
+            engine: engine.unwrap_or_else(|| LitStr::new("MergeTree()", Span::call_site())),
+            order_by,
+            partition_by,
+            primary_key,
+            ttl,
+            child_tables
+        })
+    }
+
+    fn create_sql(&self, columns: &[Column]) -> String {
+        let columns = columns
+            .iter()
+            .map(|column| {
+                let codec = column
+                    .codec
+                    .as_ref()
+                    .map(|codec| format!(" CODEC({codec})"))
+                    .unwrap_or_default();
+                format!("    `{}` {}{codec}", column.name, column.column_type)
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+
+        let mut create_sql = format!(
+            "CREATE TABLE IF NOT EXISTS {}.{}\n(\n{columns}\n)\nENGINE = {}",
+            self.database.value(),
+            self.table_name.value(),
+            self.engine.value()
+        );
+
+        let clauses = [("ORDER BY", &self.order_by), ("PARTITION BY", &self.partition_by), ("PRIMARY KEY", &self.primary_key), ("TTL", &self.ttl)];
+        for (clause, value) in clauses {
+            if let Some(value) = value {
+                create_sql.push_str(&format!("\n{clause} {}", value.value()));
+            }
+        }
+
+        create_sql
+    }
+}
+
+/// a column of the table, from a field of the struct
+struct Column {
+    name:        String,
+    column_type: String,
+    codec:       Option<String>
+}
+
+/// the columns of the named fields, named like their serde name (with the
+/// `rename_all` of the struct). the fields serde skips aren't columns
+fn columns(input: &DeriveInput) -> syn::Result<Vec<Column>> {
+    let Data::Struct(data) = &input.data else { return Err(syn::Error::new(input.span(), "ClickhouseTable can only be derived for structs")) };
+    let Fields::Named(fields) = &data.fields else { return Err(syn::Error::new(input.span(), "ClickhouseTable needs a struct with named fields")) };
+    let rename_all = rename_all(input)?;
+
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap().to_string();
+            let ident = ident.trim_start_matches("r#");
+            let mut name = rename_all.map_or_else(|| ident.to_string(), |rule| rule.apply(ident));
+            let (mut column_type, mut codec, mut skipped) = (None, None, false);
+
+            for attr in &field.attrs {
+                if attr.path().is_ident("ch") {
+                    attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("type") {
+                            column_type = Some(meta.value()?.parse::<LitStr>()?.value());
+                        } else if meta.path.is_ident("codec") {
+                            codec = Some(meta.value()?.parse::<LitStr>()?.value());
+                        } else {
+                            return Err(meta.error("unknown column attribute"))
+                        }
+
+                        Ok(())
+                    })?;
+                } else if attr.path().is_ident("serde") {
+                    // the rows are (de)serialized with the serde names
+                    attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+                            name = meta.value()?.parse::<LitStr>()?.value();
+                        } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                            skipped = true;
+                        } else if meta.path.is_ident("flatten") {
+                            return Err(meta.error("flattened fields aren't supported by ClickhouseTable"))
+                        } else if meta.input.peek(Token![=]) {
+                            meta.value()?.parse::<Expr>()?;
+                        } else if meta.input.peek(token::Paren) {
+                            let _nested;
+                            parenthesized!(_nested in meta.input);
+                        }
+
+                        Ok(())
+                    })?;
+                }
+            }
+
+            if skipped {
+                return Ok(None)
+            }
+
+            let column_type = match column_type {
+                Some(column_type) => column_type,
+                None => clickhouse_type(&field.ty).ok_or_else(|| {
+                    syn::Error::new(field.ty.span(), "can't infer the clickhouse type of the field, set it with `#[ch(type = \"...\")]`")
+                })?
+            };
+
+            Ok(Some(Column { name, column_type, codec }))
+        })
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+/// the `#[serde(rename_all = "...")]` of the struct, its `serialize` rule if
+/// it has one for each direction
+fn rename_all(input: &DeriveInput) -> syn::Result<Option<RenameRule>> {
+    let mut rule = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("rename_all") {
+                if meta.input.peek(Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                } else if meta.input.peek(token::Paren) {
+                    let _nested;
+                    parenthesized!(_nested in meta.input);
+                }
+            } else if meta.input.peek(Token![=]) {
+                rule = Some(RenameRule::parse(&meta.value()?.parse()?)?);
+            } else {
+                meta.parse_nested_meta(|direction| {
+                    let value = direction.value()?.parse::<LitStr>()?;
+                    if direction.path.is_ident("serialize") {
+                        rule = Some(RenameRule::parse(&value)?);
+                    }
+
+                    Ok(())
+                })?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(rule)
+}
+
+/// a serde `rename_all` rule, applied to the snake_case field names
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> syn::Result<Self> {
+        let rule = match rule.value().as_str() {
+            "lowercase" | "snake_case" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return Err(syn::Error::new(rule.span(), "unknown serde rename_all rule"))
+        };
+
+        Ok(rule)
+    }
+
+    fn apply(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+
+                pascal
+            }
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply(field);
+                pascal[..1].to_ascii_lowercase() + &pascal[1..]
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake.apply(field).replace('_', "-")
+        }
+    }
+}
+
+/// the clickhouse type of the common rust types
+fn clickhouse_type(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => {
+            let segment = path.path.segments.last()?;
+            let args = match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None
+                    })
+                    .collect(),
+                _ => Vec::new()
+            };
+
+            let column_type = match (segment.ident.to_string().as_str(), args.as_slice()) {
+                ("u8", []) => "UInt8".to_string(),
+                ("u16", []) => "UInt16".to_string(),
+                ("u32", []) => "UInt32".to_string(),
+                ("u64", []) => "UInt64".to_string(),
+                ("u128", []) => "UInt128".to_string(),
+                ("i8", []) => "Int8".to_string(),
+                ("i16", []) => "Int16".to_string(),
+                ("i32", []) => "Int32".to_string(),
+                ("i64", []) => "Int64".to_string(),
+                ("i128", []) => "Int128".to_string(),
+                ("f32", []) => "Float32".to_string(),
+                ("f64", []) => "Float64".to_string(),
+                ("bool", []) => "Bool".to_string(),
+                ("String", []) => "String".to_string(),
+                ("Option", [inner]) => format!("Nullable({})", clickhouse_type(inner)?),
+                ("Vec", [inner]) => format!("Array({})", clickhouse_type(inner)?),
+                ("HashMap" | "BTreeMap", [key, value]) => format!("Map({}, {})", clickhouse_type(key)?, clickhouse_type(value)?),
+                _ => return None
+            };
+
+            Some(column_type)
+        }
+        Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+            let elems = tuple
+                .elems
+                .iter()
+                .map(clickhouse_type)
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("Tuple({})", elems.join(", ")))
+        }
+        Type::Reference(reference) => clickhouse_type(&reference.elem),
+        _ => None
+    }
+}
+
+/// `snake_case` of a `CamelCase` ident
+fn snake_case(ident: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() && i != 0 && !snake.ends_with('_') {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+
+    snake
+}
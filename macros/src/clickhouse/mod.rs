@@ -1,3 +1,5 @@
+mod ddl;
+pub(crate) mod derive_table;
 pub(crate) mod remote_table;
 pub(crate) mod table;
 mod types;
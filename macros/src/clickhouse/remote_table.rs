@@ -60,11 +60,19 @@ impl RemoteClickhouseTableParse {
             .map(|table| table.into_token_stream())
             .collect_vec();
 
-        let TableMeta { table_name_str, db_table_type, database_name, table_type, file_path } = TableMeta::new(this, table_path.as_ref())?;
+        let TableMeta { table_name_str, db_table_type, database_name, table_type, file_path, columns, engine } =
+            TableMeta::new(this, table_path.as_ref())?;
+
+        let column_check = columns
+            .map(|columns| column_check(&data_type, &format!("{database_name}.{table_name_str}"), &file_path.value(), &columns))
+            .unwrap_or_default();
 
         let (table_name_str, db_table_type, table_type, file_path, other_tables_needed) =
             (table_name_str, db_table_type, table_type, file_path.into_token_stream(), quote!(&[#(#dbms::#other_tables_needed),*]));
 
+        // `include_str!` rebuilds the table when its sql file changes
+        let create_sql = if table_path.is_some() { quote!(include_str!(#file_path)) } else { quote!("") };
+
         let no_file_impls = if table_path.is_none() {
             quote! {
                 fn create_table(_database: &::db_interfaces::clickhouse::client::ClickhouseClient<#dbms>)
@@ -82,15 +90,20 @@ impl RemoteClickhouseTableParse {
                 const DATABASE_NAME: &'static str = #database_name;
                 const TABLE_NAME: &'static str = #table_name_str;
                 const FILE_PATH: &'static str = #file_path;
+                const CREATE_SQL: &'static str = #create_sql;
                 const CHILD_TABLES: &'static [#dbms] = #other_tables_needed;
                 const TABLE_TYPE: db_interfaces::clickhouse::tables::ClickhouseTableKind = #table_type;
                 const TABLE_ENUM: #dbms = #dbms::#db_table_type;
                 type ClickhouseDataType = #data_type;
 
+                #engine
+
                 #no_file_impls
             }
 
             ::db_interfaces::database_table!(#db_table_type, #data_type);
+
+            #column_check
         };
 
         #[cfg(feature = "test-utils")]
@@ -120,6 +133,47 @@ impl RemoteClickhouseTableParse {
     }
 }
 
+/// const assertion that the fields of the data type are the columns of the sql
+/// file, in the same order. rows without named fields aren't checked
+fn column_check(data_type: &TokenStream, table: &str, file_path: &str, columns: &[String]) -> TokenStream {
+    let data_type_str = data_type.to_string().replace(' ', "");
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .map_or(file_path.into(), |file_name| file_name.to_string_lossy());
+
+    let missing = columns
+        .iter()
+        .map(|column| format!("{table}: column `{column}` of {file_name} isn't a field of `{data_type_str}`"));
+    let reordered_field = format!("{table}: field `");
+    let reordered_column = format!(" of `{data_type_str}` but {file_name} has the column `");
+    let extra = format!("{table}: `{data_type_str}` has fields that aren't columns of {file_name}");
+
+    quote! {
+        const _: () = {
+            const MISSING: &[&str] = &[#(#missing),*];
+            const COLUMNS: &[&str] = &[#(#columns),*];
+            const ROW: &[&str] = ::db_interfaces::clickhouse::schema::row_columns::<#data_type>();
+
+            match ::db_interfaces::clickhouse::schema::column_mismatch(ROW, COLUMNS) {
+                ::db_interfaces::clickhouse::schema::ColumnMismatch::None => (),
+                ::db_interfaces::clickhouse::schema::ColumnMismatch::Missing(i) => panic!("{}", MISSING[i]),
+                ::db_interfaces::clickhouse::schema::ColumnMismatch::Extra => panic!("{}", #extra),
+                ::db_interfaces::clickhouse::schema::ColumnMismatch::Reordered(i) => {
+                    let message = ::db_interfaces::clickhouse::schema::ConstMessage::new()
+                        .push(#reordered_field)
+                        .push(ROW[i])
+                        .push("` is at position ")
+                        .push_usize(i)
+                        .push(#reordered_column)
+                        .push(COLUMNS[i])
+                        .push("` there, the fields must be in the order of the columns");
+                    panic!("{}", message.as_str())
+                }
+            }
+        };
+    }
+}
+
 impl Parse for RemoteClickhouseTableParse {
     fn parse(input: syn::parse::ParseStream<'_>) -> syn::Result<Self> {
         let dbms: Ident = input
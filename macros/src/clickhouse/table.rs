@@ -1,7 +1,11 @@
 use proc_macro2::{Span, TokenStream};
+use quote::quote;
 use syn::{Ident, LitStr};
 
-use super::types::ClickhouseTableKind;
+use super::{
+    ddl::{parse_columns, EngineDef},
+    types::ClickhouseTableKind
+};
 use crate::clickhouse::{remote_table::RemoteClickhouseTableParse, utils::find_file_path};
 
 pub(crate) struct TableMeta {
@@ -9,7 +13,11 @@ pub(crate) struct TableMeta {
     pub(crate) db_table_type:  Ident,
     pub(crate) database_name:  String,
     pub(crate) table_type:     TokenStream,
-    pub(crate) file_path:      LitStr
+    pub(crate) file_path:      LitStr,
+    /// the columns of the sql file the rows have, if it lists its columns
+    pub(crate) columns:        Option<Vec<String>>,
+    /// the engine metadata constants, empty if the sql has no engine
+    pub(crate) engine:         TokenStream
 }
 
 impl TableMeta {
@@ -21,10 +29,99 @@ impl TableMeta {
         let file_path_str = find_file_path(&table_name_str, &database_name, table_path);
         let file_path = LitStr::new(&file_path_str, Span::call_site());
 
-        let table_type = ClickhouseTableKind::get_table_type(&file_path_str);
+        let span = table_path.map_or(Span::call_site(), LitStr::span);
 
-        let this = Self { database_name, table_name_str, db_table_type, table_type: table_type.into(), file_path };
+        // without a sql file the table has no engine and its columns aren't checked
+        let create_sql = if file_path_str.is_empty() {
+            None
+        } else {
+            let create_sql =
+                std::fs::read_to_string(&file_path_str).map_err(|e| syn::Error::new(span, format!("failed to read {file_path_str}: {e}")))?;
+            Some(create_sql)
+        };
+
+        let table_type = match &create_sql {
+            Some(create_sql) => ClickhouseTableKind::get_table_type(create_sql)
+                .map_err(|e| syn::Error::new(span, format!("{file_path_str}: {e}")))?
+                .into(),
+            None => quote!(::db_interfaces::clickhouse::tables::ClickhouseTableKind::None)
+        };
+
+        let columns = create_sql
+            .as_deref()
+            .and_then(parse_columns)
+            .map(|columns| {
+                columns
+                    .into_iter()
+                    .filter(|column| !column.computed)
+                    .map(|column| column.name)
+                    .collect()
+            });
+
+        let engine = create_sql
+            .as_deref()
+            .and_then(EngineDef::parse)
+            .map(|engine| engine_consts(&engine))
+            .unwrap_or_default();
+
+        let this = Self { database_name, table_name_str, db_table_type, table_type, file_path, columns, engine };
 
         Ok(this)
     }
 }
+
+/// the constants of the engine arguments and table clauses
+pub(crate) fn engine_consts(engine: &EngineDef) -> TokenStream {
+    let replicated = engine.name.starts_with("Replicated") && engine.args.len() >= 2;
+    // the arguments of the engine itself, after the zookeeper path and replica
+    let engine_args = if replicated { &engine.args[2..] } else { &engine.args[..] };
+
+    let replication = if replicated {
+        let (zookeeper_path, replica) = (&engine.args[0], &engine.args[1]);
+        quote!(Some(::db_interfaces::clickhouse::tables::ReplicatedEngine { zookeeper_path: #zookeeper_path, replica: #replica }))
+    } else {
+        quote!(None)
+    };
+
+    let distributed = match engine.args.as_slice() {
+        [cluster, database, table, rest @ ..] if engine.name == "Distributed" => {
+            let sharding_key = option(rest.first());
+            quote!(Some(::db_interfaces::clickhouse::tables::DistributedEngine {
+                cluster: #cluster,
+                database: #database,
+                table: #table,
+                sharding_key: #sharding_key
+            }))
+        }
+        _ => quote!(None)
+    };
+
+    let version_column = option(
+        engine
+            .name
+            .ends_with("ReplacingMergeTree")
+            .then(|| engine_args.first())
+            .flatten()
+    );
+    let order_by = option(engine.order_by.as_ref());
+    let partition_by = option(engine.partition_by.as_ref());
+    let primary_key = option(engine.primary_key.as_ref());
+    let ttl = option(engine.ttl.as_ref());
+
+    quote! {
+        const REPLICATION: Option<::db_interfaces::clickhouse::tables::ReplicatedEngine> = #replication;
+        const DISTRIBUTED: Option<::db_interfaces::clickhouse::tables::DistributedEngine> = #distributed;
+        const VERSION_COLUMN: Option<&'static str> = #version_column;
+        const ORDER_BY: Option<&'static str> = #order_by;
+        const PARTITION_BY: Option<&'static str> = #partition_by;
+        const PRIMARY_KEY: Option<&'static str> = #primary_key;
+        const TTL: Option<&'static str> = #ttl;
+    }
+}
+
+fn option(value: Option<&String>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None)
+    }
+}
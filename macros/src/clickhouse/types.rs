@@ -1,5 +1,13 @@
+use once_cell::sync::Lazy;
 use proc_macro2::TokenStream;
 use quote::quote;
+use regex::Regex;
+
+use super::ddl::{first_statement, EngineDef};
+
+static CREATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\s*CREATE\s+(?:OR\s+REPLACE\s+)?(TABLE|TEMPORARY\s+TABLE|MATERIALIZED\s+VIEW|VIEW|DICTIONARY)\b").unwrap());
+static TABLE_FUNCTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bAS\s+(remote|remoteSecure)\s*\(").unwrap());
 
 pub(crate) enum ClickhouseTableKind {
     Distributed,
@@ -8,68 +16,90 @@ pub(crate) enum ClickhouseTableKind {
     ReplicatedMergeTree,
     ReplicatedAggregatingMergeTree,
     ReplicatedReplacingMergeTree,
+    ReplicatedSummingMergeTree,
+    ReplicatedCollapsingMergeTree,
+    ReplicatedVersionedCollapsingMergeTree,
     MergeTree,
     AggregatingMergeTree,
     ReplacingMergeTree,
+    SummingMergeTree,
+    CollapsingMergeTree,
+    VersionedCollapsingMergeTree,
     MaterializedView,
-    Null
+    View,
+    Dictionary,
+    Null,
+    Log,
+    Memory,
+    Join,
+    Set,
+    Buffer,
+    Kafka
 }
 
 impl ClickhouseTableKind {
-    pub(crate) fn get_table_type(file_path: &str) -> Self {
-        let file_str = std::fs::read_to_string(file_path).unwrap_or_else(|_| panic!("Failed to read {}", file_path));
-        if file_str.contains(&ClickhouseTableKind::Distributed.to_string()) {
-            ClickhouseTableKind::Distributed
-        } else if file_str.contains(&ClickhouseTableKind::RemoteSecure.to_string()) {
-            ClickhouseTableKind::RemoteSecure
-        } else if file_str.contains(&ClickhouseTableKind::Remote.to_string()) {
-            ClickhouseTableKind::Remote
-        } else if file_str.contains(&ClickhouseTableKind::ReplicatedMergeTree.to_string()) {
-            ClickhouseTableKind::ReplicatedMergeTree
-        } else if file_str.contains(&ClickhouseTableKind::ReplicatedAggregatingMergeTree.to_string()) {
-            ClickhouseTableKind::ReplicatedAggregatingMergeTree
-        } else if file_str.contains(&ClickhouseTableKind::ReplicatedReplacingMergeTree.to_string()) {
-            ClickhouseTableKind::ReplicatedReplacingMergeTree
-        } else if file_str.contains(&ClickhouseTableKind::ReplacingMergeTree.to_string()) {
-            ClickhouseTableKind::ReplacingMergeTree
-        } else if file_str.contains(&ClickhouseTableKind::AggregatingMergeTree.to_string()) {
-            ClickhouseTableKind::AggregatingMergeTree
-        } else if file_str.contains(&ClickhouseTableKind::MergeTree.to_string()) {
-            ClickhouseTableKind::MergeTree
-        } else if file_str.contains(&ClickhouseTableKind::MaterializedView.to_string()) {
-            ClickhouseTableKind::MaterializedView
-        } else if file_str.contains(&ClickhouseTableKind::Null.to_string()) {
-            ClickhouseTableKind::Null
-        } else {
-            panic!("None of the table engines match!")
+    /// the engine of the (first) `CREATE` statement of the sql
+    pub(crate) fn get_table_type(create_sql: &str) -> Result<Self, String> {
+        let statement = first_statement(create_sql);
+        let Some(create) = CREATE.captures(&statement) else { return Err("the sql file doesn't start with a CREATE statement".to_string()) };
+
+        let object = create.get(1).map_or(String::new(), |object| {
+            object
+                .as_str()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_uppercase()
+        });
+        match object.as_str() {
+            "MATERIALIZED VIEW" => return Ok(ClickhouseTableKind::MaterializedView),
+            "VIEW" => return Ok(ClickhouseTableKind::View),
+            "DICTIONARY" => return Ok(ClickhouseTableKind::Dictionary),
+            _ => ()
         }
-    }
-}
 
-#[allow(clippy::to_string_trait_impl)]
-impl ToString for ClickhouseTableKind {
-    fn to_string(&self) -> String {
-        let val: &'static str = self.into();
-        val.to_string()
-    }
-}
+        if let Some(engine) = EngineDef::parse(create_sql) {
+            return Self::from_engine(&engine.name).ok_or_else(|| format!("unsupported table engine `{}`", engine.name))
+        }
 
-impl From<&ClickhouseTableKind> for &'static str {
-    fn from(val: &ClickhouseTableKind) -> Self {
-        match val {
-            ClickhouseTableKind::Distributed => "Distributed",
-            ClickhouseTableKind::Remote => "remote",
-            ClickhouseTableKind::RemoteSecure => "remoteSecure",
-            ClickhouseTableKind::ReplicatedMergeTree => "ReplicatedMergeTree",
-            ClickhouseTableKind::ReplicatedAggregatingMergeTree => "ReplicatedAggregatingMergeTree",
-            ClickhouseTableKind::ReplicatedReplacingMergeTree => "ReplicatedReplacingMergeTree",
-            ClickhouseTableKind::MergeTree => "MergeTree",
-            ClickhouseTableKind::AggregatingMergeTree => "AggregatingMergeTree",
-            ClickhouseTableKind::ReplacingMergeTree => "ReplacingMergeTree",
-            ClickhouseTableKind::MaterializedView => "CREATE MATERIALIZED VIEW",
-            ClickhouseTableKind::Null => "Null"
+        // `CREATE TABLE t AS remote(...)`
+        match TABLE_FUNCTION
+            .captures(&statement)
+            .and_then(|function| function.get(1))
+        {
+            Some(function) if function.as_str().eq_ignore_ascii_case("remoteSecure") => Ok(ClickhouseTableKind::RemoteSecure),
+            Some(_) => Ok(ClickhouseTableKind::Remote),
+            None => Err("the CREATE statement has no ENGINE clause".to_string())
         }
     }
+
+    fn from_engine(engine: &str) -> Option<Self> {
+        let kind = match engine {
+            "Distributed" => ClickhouseTableKind::Distributed,
+            "ReplicatedMergeTree" => ClickhouseTableKind::ReplicatedMergeTree,
+            "ReplicatedAggregatingMergeTree" => ClickhouseTableKind::ReplicatedAggregatingMergeTree,
+            "ReplicatedReplacingMergeTree" => ClickhouseTableKind::ReplicatedReplacingMergeTree,
+            "ReplicatedSummingMergeTree" => ClickhouseTableKind::ReplicatedSummingMergeTree,
+            "ReplicatedCollapsingMergeTree" => ClickhouseTableKind::ReplicatedCollapsingMergeTree,
+            "ReplicatedVersionedCollapsingMergeTree" => ClickhouseTableKind::ReplicatedVersionedCollapsingMergeTree,
+            "MergeTree" => ClickhouseTableKind::MergeTree,
+            "AggregatingMergeTree" => ClickhouseTableKind::AggregatingMergeTree,
+            "ReplacingMergeTree" => ClickhouseTableKind::ReplacingMergeTree,
+            "SummingMergeTree" => ClickhouseTableKind::SummingMergeTree,
+            "CollapsingMergeTree" => ClickhouseTableKind::CollapsingMergeTree,
+            "VersionedCollapsingMergeTree" => ClickhouseTableKind::VersionedCollapsingMergeTree,
+            "Null" => ClickhouseTableKind::Null,
+            "Log" => ClickhouseTableKind::Log,
+            "Memory" => ClickhouseTableKind::Memory,
+            "Join" => ClickhouseTableKind::Join,
+            "Set" => ClickhouseTableKind::Set,
+            "Buffer" => ClickhouseTableKind::Buffer,
+            "Kafka" => ClickhouseTableKind::Kafka,
+            _ => return None
+        };
+
+        Some(kind)
+    }
 }
 
 impl From<ClickhouseTableKind> for TokenStream {
@@ -93,6 +123,15 @@ impl From<ClickhouseTableKind> for TokenStream {
             ClickhouseTableKind::ReplicatedReplacingMergeTree => {
                 quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::ReplicatedReplacingMergeTree }
             }
+            ClickhouseTableKind::ReplicatedSummingMergeTree => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::ReplicatedSummingMergeTree }
+            }
+            ClickhouseTableKind::ReplicatedCollapsingMergeTree => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::ReplicatedCollapsingMergeTree }
+            }
+            ClickhouseTableKind::ReplicatedVersionedCollapsingMergeTree => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::ReplicatedVersionedCollapsingMergeTree }
+            }
             ClickhouseTableKind::MergeTree => {
                 quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::MergeTree }
             }
@@ -102,12 +141,45 @@ impl From<ClickhouseTableKind> for TokenStream {
             ClickhouseTableKind::ReplacingMergeTree => {
                 quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::ReplacingMergeTree }
             }
+            ClickhouseTableKind::SummingMergeTree => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::SummingMergeTree }
+            }
+            ClickhouseTableKind::CollapsingMergeTree => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::CollapsingMergeTree }
+            }
+            ClickhouseTableKind::VersionedCollapsingMergeTree => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::VersionedCollapsingMergeTree }
+            }
             ClickhouseTableKind::MaterializedView => {
                 quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::MaterializedView }
             }
+            ClickhouseTableKind::View => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::View }
+            }
+            ClickhouseTableKind::Dictionary => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Dictionary }
+            }
             ClickhouseTableKind::Null => {
                 quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Null }
             }
+            ClickhouseTableKind::Log => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Log }
+            }
+            ClickhouseTableKind::Memory => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Memory }
+            }
+            ClickhouseTableKind::Join => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Join }
+            }
+            ClickhouseTableKind::Set => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Set }
+            }
+            ClickhouseTableKind::Buffer => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Buffer }
+            }
+            ClickhouseTableKind::Kafka => {
+                quote! { ::db_interfaces::clickhouse::tables::ClickhouseTableKind::Kafka }
+            }
         }
     }
 }
@@ -36,3 +36,45 @@ pub fn remote_clickhouse_table(input: TokenStream) -> TokenStream {
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
+
+#[proc_macro_derive(ClickhouseTable, attributes(ch))]
+/// derives `ClickhouseTable` (and `DatabaseTable`) for a row struct and
+/// generates its `CREATE TABLE` statement as `Self::CREATE_SQL`, so the table
+/// doesn't need a sql file. The struct is the table and its rows, it's the
+/// variant of the DBMS (`clickhouse_dbms!(DBMS, [Table])`) and must implement
+/// `Default`
+///
+/// Struct attributes:
+/// - `dbms = DBMS` (required) - enum name of the DBMS
+/// - `database = "db"` (required) - name of the database
+/// - `table = "name"` - name of the table, the struct name in snake_case by
+///   default
+/// - `engine = "ReplicatedMergeTree('/path', '{replica}')"` - `MergeTree()` by
+///   default
+/// - `order_by`, `partition_by`, `primary_key`, `ttl` - the expressions of the
+///   table clauses
+/// - `child_tables(Table0, Table1)` - tables created along with the table
+///
+/// Field attributes:
+/// - `type = "LowCardinality(String)"` - the clickhouse type, inferred for
+///   integers, floats, `bool`, `String`, `Option`, `Vec`, maps and tuples
+/// - `codec = "ZSTD(1)"`
+///
+/// Example:
+/// ```ignore
+/// #[derive(Default, Serialize, Deserialize, Row, ClickhouseTable)]
+/// #[ch(dbms = DBMS, database = "db", engine = "ReplacingMergeTree(updated_at)", order_by = "(pair, id)")]
+/// pub struct Trades {
+///     id:         u64,
+///     #[ch(type = "LowCardinality(String)")]
+///     pair:       String,
+///     #[ch(codec = "ZSTD(1)")]
+///     price:      f64,
+///     updated_at: u64
+/// }
+/// ```
+pub fn derive_clickhouse_table(input: TokenStream) -> TokenStream {
+    clickhouse::derive_table::derive_clickhouse_table(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
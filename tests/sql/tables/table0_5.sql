@@ -0,0 +1,9 @@
+-- summed rows of the Distributed table0_1, not a plain MergeTree
+CREATE TABLE database1.table0_5 ON CLUSTER cluster0
+(
+    `distributed_at` UInt64,
+    `type0` Nullable(String) COMMENT 'Null when unknown, ENGINE = Memory',
+    `type1` UInt64
+) 
+ENGINE = ReplicatedSummingMergeTree('/path/to/zookeeper/', '{replica}')
+ORDER BY `distributed_at`
@@ -0,0 +1,6 @@
+CREATE MATERIALIZED VIEW database1.table0_6 ON CLUSTER cluster0 TO database1.table0_5
+AS SELECT 
+    toUInt64(now()) AS distributed_at,
+    type0,
+    type1
+FROM database1.table0_2
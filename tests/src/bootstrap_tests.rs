@@ -0,0 +1,79 @@
+use clickhouse::Row;
+use db_interfaces::{
+    clickhouse::{
+        bootstrap::{creation_order, drop_statement, if_not_exists},
+        dbms::ClickhouseDBMS,
+        errors::ClickhouseError
+    },
+    clickhouse_dbms, ClickhouseTable
+};
+use serde::{Deserialize, Serialize};
+
+use crate::macro_tests::Dbms0;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Row, ClickhouseTable)]
+#[ch(dbms = Dbms2, database = "database3", engine = "Memory", child_tables(Orders))]
+pub struct Fills {
+    id: u64
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Row, ClickhouseTable)]
+#[ch(dbms = Dbms2, database = "database3", engine = "Memory", child_tables(Fills))]
+pub struct Orders {
+    id: u64
+}
+
+clickhouse_dbms!(Dbms2, [Fills, Orders]);
+
+#[test]
+fn test_creation_order() {
+    let tables = Dbms0::all_tables();
+    let levels = creation_order(&tables).unwrap();
+
+    assert_eq!(
+        levels,
+        [
+            vec![
+                &Dbms0::Database1Table0_1,
+                &Dbms0::Database1Table0_2,
+                &Dbms0::Database1Sub_Db0Table0_3,
+                &Dbms0::Database1Sub_Db0Table0_4,
+                &Dbms0::Database1Table0_5,
+                &Dbms0::Database1Table0_6
+            ],
+            vec![&Dbms0::Database0Table0_0]
+        ]
+    );
+}
+
+#[test]
+fn test_creation_order_cycle() {
+    let tables = Dbms2::all_tables();
+
+    match creation_order(&tables) {
+        Err(ClickhouseError::DependencyCycle { tables }) => assert_eq!(tables, ["database3.fills", "database3.orders"]),
+        other => panic!("expected a dependency cycle, got {other:?}")
+    }
+}
+
+#[test]
+fn test_if_not_exists() {
+    assert_eq!(
+        if_not_exists("-- comment\nCREATE TABLE database1.table0_5 ON CLUSTER cluster0 (id UInt64) ENGINE = Memory"),
+        "-- comment\nCREATE TABLE IF NOT EXISTS database1.table0_5 ON CLUSTER cluster0 (id UInt64) ENGINE = Memory"
+    );
+    assert_eq!(
+        if_not_exists("create materialized view db.view0 TO db.table0 AS SELECT 1"),
+        "create materialized view IF NOT EXISTS db.view0 TO db.table0 AS SELECT 1"
+    );
+    assert_eq!(if_not_exists("CREATE TABLE IF NOT EXISTS db.table0 (id UInt64)"), "CREATE TABLE IF NOT EXISTS db.table0 (id UInt64)");
+    assert_eq!(if_not_exists("CREATE OR REPLACE VIEW db.view0 AS SELECT 1"), "CREATE OR REPLACE VIEW db.view0 AS SELECT 1");
+}
+
+#[test]
+fn test_drop_statement() {
+    assert_eq!(drop_statement(&Dbms0::Database1Table0_5), "DROP TABLE IF EXISTS database1.table0_5 ON CLUSTER cluster0 SYNC");
+    assert_eq!(drop_statement(&Dbms0::Database1Table0_6), "DROP VIEW IF EXISTS database1.table0_6 ON CLUSTER cluster0 SYNC");
+    assert_eq!(drop_statement(&Dbms0::Database1Sub_Db0Table0_3), "DROP TABLE IF EXISTS database1.`sub_db0.table0_3` ON CLUSTER cluster0 SYNC");
+    assert_eq!(drop_statement(&Dbms2::Orders), "DROP TABLE IF EXISTS database3.orders SYNC");
+}
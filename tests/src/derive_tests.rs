@@ -0,0 +1,101 @@
+use clickhouse::Row;
+use db_interfaces::{
+    clickhouse::tables::{ClickhouseTable, ClickhouseTableKind, ReplicatedEngine},
+    clickhouse_dbms,
+    tables::DatabaseTable,
+    ClickhouseTable
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Row, ClickhouseTable)]
+#[ch(
+    dbms = Dbms1,
+    database = "database2",
+    engine = "ReplicatedReplacingMergeTree('/clickhouse/tables/trades', '{replica}', updated_at)",
+    order_by = "(pair, id)",
+    partition_by = "toYYYYMM(toDateTime(updated_at))",
+    ttl = "toDateTime(updated_at) + INTERVAL 30 DAY",
+    child_tables(TradeCounts)
+)]
+pub struct Trades {
+    id:         u64,
+    #[ch(type = "LowCardinality(String)")]
+    pair:       String,
+    #[ch(codec = "ZSTD(1)")]
+    price:      f64,
+    #[serde(rename = "size")]
+    amount:     Option<f32>,
+    tags:       Vec<(String, i32)>,
+    #[serde(skip)]
+    cached:     bool,
+    updated_at: u64
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Row, ClickhouseTable)]
+#[ch(dbms = Dbms1, database = "database2", table = "trade_counts_by_pair", engine = "Memory")]
+pub struct TradeCounts {
+    pair:  String,
+    count: u64
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Row, ClickhouseTable)]
+#[ch(dbms = Dbms1, database = "database2", engine = "Memory")]
+#[serde(rename_all = "camelCase")]
+pub struct TradeFees {
+    trade_id:   u64,
+    #[serde(rename = "fee")]
+    fee_amount: f64
+}
+
+clickhouse_dbms!(Dbms1, "cluster1", [Trades, TradeCounts, TradeFees]);
+
+#[test]
+fn test_derive_create_sql() {
+    assert_eq!(
+        Trades::CREATE_SQL,
+        "CREATE TABLE IF NOT EXISTS database2.trades
+(
+    `id` UInt64,
+    `pair` LowCardinality(String),
+    `price` Float64 CODEC(ZSTD(1)),
+    `size` Nullable(Float32),
+    `tags` Array(Tuple(String, Int32)),
+    `updated_at` UInt64
+)
+ENGINE = ReplicatedReplacingMergeTree('/clickhouse/tables/trades', '{replica}', updated_at)
+ORDER BY (pair, id)
+PARTITION BY toYYYYMM(toDateTime(updated_at))
+TTL toDateTime(updated_at) + INTERVAL 30 DAY"
+    );
+
+    assert_eq!(
+        TradeCounts::CREATE_SQL,
+        "CREATE TABLE IF NOT EXISTS database2.trade_counts_by_pair\n(\n    `pair` String,\n    `count` UInt64\n)\nENGINE = Memory"
+    );
+
+    // the columns are named like the serialized fields
+    assert_eq!(
+        TradeFees::CREATE_SQL,
+        "CREATE TABLE IF NOT EXISTS database2.trade_fees\n(\n    `tradeId` UInt64,\n    `fee` Float64\n)\nENGINE = Memory"
+    );
+}
+
+#[test]
+fn test_derive_table_consts() {
+    assert_eq!(<Trades as DatabaseTable>::NAME, "Trades");
+    assert_eq!(Trades::DATABASE_NAME, "database2");
+    assert_eq!(Trades::TABLE_NAME, "trades");
+    assert_eq!(Trades::full_name(), "database2.trades");
+    assert_eq!(Trades::CHILD_TABLES, [Dbms1::TradeCounts]);
+    assert_eq!(Trades::TABLE_TYPE, ClickhouseTableKind::ReplicatedReplacingMergeTree);
+    assert_eq!(Trades::TABLE_ENUM, Dbms1::Trades);
+    assert_eq!(Trades::REPLICATION, Some(ReplicatedEngine { zookeeper_path: "/clickhouse/tables/trades", replica: "{replica}" }));
+    assert_eq!(Trades::VERSION_COLUMN, Some("updated_at"));
+    assert_eq!(Trades::ORDER_BY, Some("(pair, id)"));
+    assert_eq!(Trades::PARTITION_BY, Some("toYYYYMM(toDateTime(updated_at))"));
+    assert_eq!(Trades::TTL, Some("toDateTime(updated_at) + INTERVAL 30 DAY"));
+
+    assert_eq!(TradeCounts::TABLE_NAME, "trade_counts_by_pair");
+    assert_eq!(TradeCounts::TABLE_TYPE, ClickhouseTableKind::Memory);
+    assert_eq!(TradeCounts::ORDER_BY, None);
+}
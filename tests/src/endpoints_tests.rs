@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use clickhouse::Client;
+use db_interfaces::clickhouse::endpoints::{Endpoint, Endpoints, LoadBalancing, LoadBalancingStrategy};
+
+fn endpoints(strategy: LoadBalancingStrategy, failure_threshold: u32, ejection: Duration) -> Endpoints {
+    let endpoints = (0..3)
+        .map(|i| Endpoint::new(format!("http://replica{i}:8123"), Client::default()))
+        .collect();
+
+    Endpoints::new(endpoints, LoadBalancing { strategy, failure_threshold, ejection })
+}
+
+fn candidate_urls(endpoints: &Endpoints) -> Vec<&str> {
+    endpoints
+        .candidates()
+        .into_iter()
+        .map(|endpoint| endpoint.url.as_str())
+        .collect()
+}
+
+#[test]
+fn test_endpoints_round_robin() {
+    let endpoints = endpoints(LoadBalancingStrategy::RoundRobin, 3, Duration::from_secs(30));
+
+    assert_eq!(candidate_urls(&endpoints), ["http://replica0:8123", "http://replica1:8123", "http://replica2:8123"]);
+    assert_eq!(candidate_urls(&endpoints), ["http://replica1:8123", "http://replica2:8123", "http://replica0:8123"]);
+    assert_eq!(candidate_urls(&endpoints), ["http://replica2:8123", "http://replica0:8123", "http://replica1:8123"]);
+    assert_eq!(candidate_urls(&endpoints)[0], "http://replica0:8123");
+}
+
+#[test]
+fn test_endpoints_first_healthy_ejection() {
+    let endpoints = endpoints(LoadBalancingStrategy::FirstHealthy, 2, Duration::from_secs(30));
+    let primary = endpoints.primary();
+
+    primary.record_failure(&endpoints.load_balancing);
+    assert!(primary.is_available());
+    assert_eq!(candidate_urls(&endpoints)[0], "http://replica0:8123");
+
+    primary.record_failure(&endpoints.load_balancing);
+    assert!(!primary.is_available());
+    assert_eq!(candidate_urls(&endpoints), ["http://replica1:8123", "http://replica2:8123"]);
+
+    primary.record_success();
+    assert_eq!(primary.consecutive_failures(), 0);
+    assert_eq!(candidate_urls(&endpoints)[0], "http://replica0:8123");
+}
+
+#[test]
+fn test_endpoints_reprobe_and_all_ejected() {
+    let endpoints = endpoints(LoadBalancingStrategy::FirstHealthy, 1, Duration::ZERO);
+
+    // with no ejection time the endpoint is probed by the next request
+    endpoints
+        .primary()
+        .record_failure(&endpoints.load_balancing);
+    assert!(endpoints.primary().is_available());
+
+    let endpoints = self::endpoints(LoadBalancingStrategy::Random, 1, Duration::from_secs(30));
+    endpoints
+        .iter()
+        .for_each(|endpoint| endpoint.record_failure(&endpoints.load_balancing));
+
+    // every endpoint is ejected, they're all tried anyway
+    assert_eq!(endpoints.candidates().len(), 3);
+}
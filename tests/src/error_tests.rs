@@ -0,0 +1,22 @@
+use db_interfaces::{clickhouse::errors::ClickhouseError, errors::DatabaseError};
+
+#[test]
+fn test_server_exception_parsing() {
+    let error = ClickhouseError::from_server_response(
+        "Code: 60. DB::Exception: Table default.missing does not exist. (UNKNOWN_TABLE) (version 23.8.2.7 (official build))"
+    )
+    .unwrap();
+    assert!(matches!(error, ClickhouseError::UnknownTable { code: 60, .. }));
+    assert_eq!(error.message(), Some("Table default.missing does not exist. (UNKNOWN_TABLE) (version 23.8.2.7 (official build))"));
+
+    let error = ClickhouseError::from_server_response("Code: 252, e.displayText() = DB::Exception: Too many parts (300) (version 21.3.1)").unwrap();
+    assert!(matches!(error, ClickhouseError::TooManyParts { code: 252, .. }));
+
+    let error: DatabaseError = ClickhouseError::from_server_response("Code: 1000. DB::Exception: Poco::Exception")
+        .unwrap()
+        .into();
+    assert_eq!(error.server_code(), Some(1000));
+    assert_eq!(error.server_message(), Some("Poco::Exception"));
+
+    assert!(ClickhouseError::from_server_response("502 Bad Gateway").is_none());
+}
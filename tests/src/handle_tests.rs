@@ -0,0 +1,93 @@
+use clickhouse::Row;
+use db_interfaces::{
+    clickhouse::{errors::ClickhouseError, handle::TableHandle},
+    clickhouse_dbms,
+    errors::DatabaseError,
+    test_utils::mock::{client::MockDatabase, types::RecordedQuery},
+    ClickhouseTable
+};
+use serde::{Deserialize, Serialize};
+
+use crate::macro_tests::{Database1Table0_1, Dbms0};
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, Row, ClickhouseTable)]
+#[ch(dbms = Dbms3, database = "database4", engine = "MergeTree()", order_by = "account")]
+pub struct Balances {
+    account: String,
+    amount:  u64
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, Row, ClickhouseTable)]
+#[ch(dbms = Dbms3, database = "database4", engine = "Null")]
+pub struct BalanceEvents {
+    account: String
+}
+
+clickhouse_dbms!(Dbms3, [Balances, BalanceEvents]);
+
+fn balances() -> Vec<Balances> {
+    (0..3)
+        .map(|i| Balances { account: format!("account{i}"), amount: i * 10 })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_table_handle() {
+    let mock = MockDatabase::<Dbms3>::new();
+    let balances_table = TableHandle::<Balances, Dbms3, _>::new(&mock);
+
+    let rows = balances();
+    balances_table.insert_many(&rows[..2]).await.unwrap();
+    balances_table.insert(&rows[2]).await.unwrap();
+    assert_eq!(mock.inserted::<Balances>(), rows);
+
+    mock.on_query("SELECT count() FROM database4.balances", &[3u64])
+        .unwrap();
+    assert_eq!(balances_table.count().await.unwrap(), 3);
+
+    mock.on_query("SELECT `account`, `amount` FROM database4.balances WHERE amount > ?", &rows[1..])
+        .unwrap();
+    mock.on_query("SELECT `account`, `amount` FROM database4.balances", &rows)
+        .unwrap();
+    assert_eq!(balances_table.select_all().await.unwrap(), rows);
+    assert_eq!(
+        balances_table
+            .select_where("amount > ?", &5u64)
+            .await
+            .unwrap(),
+        rows[1..]
+    );
+    assert_eq!(mock.queries().last().unwrap().params, [5u64]);
+
+    mock.on_query("EXISTS TABLE database4.balances", &[1u8])
+        .unwrap();
+    assert!(balances_table.exists().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_table_handle_delete_where() {
+    let mock = MockDatabase::<Dbms0>::new();
+
+    // the rows of the Distributed table are deleted from its local table
+    TableHandle::<Database1Table0_1, Dbms0, _>::new(&mock)
+        .delete_where("type1 = ?", &7u64)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        mock.executed(),
+        [RecordedQuery { sql: "DELETE FROM database1.table0_2 ON CLUSTER cluster0 WHERE type1 = ?".to_string(), params: vec![7u64.into()] }]
+    );
+}
+
+#[tokio::test]
+async fn test_table_handle_delete_where_no_storage() {
+    let mock = MockDatabase::<Dbms3>::new();
+
+    let deleted = TableHandle::<BalanceEvents, Dbms3, _>::new(&mock)
+        .delete_where("account = ?", &"account0")
+        .await;
+
+    assert!(matches!(deleted, Err(DatabaseError::ClickhouseError(ClickhouseError::NoStorageTable(table))) if table == "database4.balance_events"));
+    assert!(mock.executed().is_empty());
+}
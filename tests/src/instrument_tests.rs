@@ -0,0 +1,32 @@
+use db_interfaces::{
+    clickhouse::{
+        errors::ClickhouseError,
+        instrument::{error_kind, query_table, truncate}
+    },
+    errors::DatabaseError,
+    inserts::InsertSummary
+};
+
+#[test]
+fn test_query_table() {
+    assert_eq!(query_table("SELECT * FROM db.table0 WHERE id = ?"), Some("db.table0"));
+    assert_eq!(query_table("insert into `db`.`table1` values (1)"), Some("`db`.`table1`"));
+    assert_eq!(query_table("OPTIMIZE TABLE db.table2 FINAL"), Some("db.table2"));
+    assert_eq!(query_table("SELECT 1"), None);
+}
+
+#[test]
+fn test_truncate() {
+    assert_eq!(truncate("SELECT 1", 256), "SELECT 1");
+    assert_eq!(truncate("SELECT 1", 6), "SELECT");
+    assert_eq!(truncate("SELECT 'é'", 9), "SELECT '");
+}
+
+#[test]
+fn test_error_kind() {
+    let error = DatabaseError::from(ClickhouseError::TooManyParts { code: 252, message: String::new() });
+    assert_eq!(error_kind(&error), "too_many_parts");
+
+    let error = DatabaseError::PartialInsert { summary: InsertSummary::default(), error: Box::new(ClickhouseError::Cancelled.into()) };
+    assert_eq!(error_kind(&error), "cancelled");
+}
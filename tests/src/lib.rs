@@ -1,2 +1,44 @@
+#[cfg(test)]
+pub mod bootstrap_tests;
+
+#[cfg(test)]
+pub mod config_tests;
+
+#[cfg(test)]
+pub mod derive_tests;
+
+#[cfg(test)]
+pub mod endpoints_tests;
+
+#[cfg(test)]
+pub mod error_tests;
+
+#[cfg(test)]
+pub mod handle_tests;
+
+#[cfg(test)]
+pub mod instrument_tests;
+
 #[cfg(test)]
 pub mod macro_tests;
+
+#[cfg(test)]
+pub mod migrations_tests;
+
+#[cfg(test)]
+pub mod mock_tests;
+
+#[cfg(test)]
+pub mod options_tests;
+
+#[cfg(test)]
+pub mod query_builder_tests;
+
+#[cfg(test)]
+pub mod retry_tests;
+
+#[cfg(test)]
+pub mod schema_tests;
+
+#[cfg(test)]
+pub mod sqlite_tests;
@@ -1,6 +1,9 @@
 use clickhouse::{DbRow, Row};
 use db_interfaces::{
-    clickhouse::tables::{ClickhouseTable, ClickhouseTableKind},
+    clickhouse::{
+        config::ClickhouseConfig,
+        tables::{ClickhouseTable, ClickhouseTableKind, DistributedEngine, ReplicatedEngine}
+    },
     clickhouse_dbms, remote_clickhouse_table
 };
 use serde::{Deserialize, Serialize};
@@ -65,14 +68,35 @@ pub struct TypeGeneric<T: Clone + Serialize + DbRow> {
     id:    u64
 }
 
+#[derive(Clone, Deserialize, Serialize, Row)]
+pub struct Type1 {
+    distributed_at: u64,
+    type0:          Option<String>,
+    type1:          u64
+}
+
 // Table0_1, Table0_2, Table0_3
-clickhouse_dbms!(Dbms0, "cluster0", [Database0Table0_0, Database1Table0_1, Database1Table0_2, Database1Sub_Db0Table0_3, Database1Sub_Db0Table0_4]);
+clickhouse_dbms!(
+    Dbms0,
+    "cluster0",
+    [
+        Database0Table0_0,
+        Database1Table0_1,
+        Database1Table0_2,
+        Database1Sub_Db0Table0_3,
+        Database1Sub_Db0Table0_4,
+        Database1Table0_5,
+        Database1Table0_6
+    ]
+);
 
 remote_clickhouse_table!(Dbms0, [Database0, Table0_0], String, "tests/sql/tables/");
 remote_clickhouse_table!(Dbms0, [Database1, Table0_1], Type0, (Database0Table0_0), "tests/sql/tables/");
 remote_clickhouse_table!(Dbms0, [Database1, Table0_2], "tests/sql/tables/");
 remote_clickhouse_table!(Dbms0, [Database1, Sub_Db0, Table0_3], Type0, "tests/sql/tables/");
 remote_clickhouse_table!(Dbms0, [Database1, Sub_Db0, Table0_4], TypeGeneric<Type0>, "tests/sql/tables/");
+remote_clickhouse_table!(Dbms0, [Database1, Table0_5], Type1, "tests/sql/tables/");
+remote_clickhouse_table!(Dbms0, [Database1, Table0_6], Type1, "tests/sql/tables/");
 
 clickhouse_table_test!(
     (Database0Table0_0),
@@ -113,3 +137,75 @@ clickhouse_table_test!(
     (TABLE_TYPE | ReplicatedReplacingMergeTree),
     (TABLE_ENUM | Database1Sub_Db0Table0_3)
 );
+
+clickhouse_table_test!(
+    (Database1Table0_5),
+    (DATABASE_NAME | "database1"),
+    (TABLE_NAME | "table0_5"),
+    (FILE_PATH | "/tests/sql/tables/table0_5.sql"),
+    (CHILD_TABLES | []),
+    (TABLE_TYPE | ReplicatedSummingMergeTree),
+    (TABLE_ENUM | Database1Table0_5)
+);
+
+clickhouse_table_test!(
+    (Database1Table0_6),
+    (DATABASE_NAME | "database1"),
+    (TABLE_NAME | "table0_6"),
+    (FILE_PATH | "/tests/sql/tables/table0_6.sql"),
+    (CHILD_TABLES | []),
+    (TABLE_TYPE | MaterializedView),
+    (TABLE_ENUM | Database1Table0_6)
+);
+
+#[test]
+fn test_engine_metadata() {
+    assert_eq!(
+        Database1Table0_1::DISTRIBUTED,
+        Some(DistributedEngine {
+            cluster:      "cluster0",
+            database:     "database1",
+            table:        "table0_2",
+            sharding_key: Some("cityHash64(`type0`)")
+        })
+    );
+    assert_eq!(Database1Table0_1::REPLICATION, None);
+    assert_eq!(Database1Table0_1::ORDER_BY, None);
+
+    assert_eq!(Database1Table0_2::REPLICATION, Some(ReplicatedEngine { zookeeper_path: "/path/to/zookeeper/", replica: "{replica}" }));
+    assert_eq!(Database1Table0_2::VERSION_COLUMN, None);
+    assert_eq!(Database1Table0_2::ORDER_BY, Some("(`type0`)"));
+    assert_eq!(Database1Table0_2::PARTITION_BY, None);
+
+    assert_eq!(Database0Table0_0::REPLICATION, None);
+    assert_eq!(Database0Table0_0::ORDER_BY, Some("`type0`"));
+
+    assert_eq!(Database1Table0_5::ORDER_BY, Some("`distributed_at`"));
+    assert_eq!(Database1Table0_6::ORDER_BY, None);
+}
+
+#[test]
+fn test_embedded_create_sql() {
+    assert_eq!(Database1Table0_1::CREATE_SQL, include_str!("../sql/tables/table0_1.sql"));
+    assert_eq!(Database1Sub_Db0Table0_3::override_file_name(), "database1.sub_db0.table0_3.sql");
+
+    let dir = std::env::temp_dir().join(format!("db_interfaces_sql_override_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("database1.table0_1.sql"), "CREATE TABLE database1.table0_1 AS database1.table0_2").unwrap();
+
+    let client = ClickhouseConfig::new("default".to_string(), String::new(), "http://localhost:8123".to_string(), false, None)
+        .with_sql_override_dir(&dir)
+        .build::<Dbms0>();
+    assert_eq!(Database1Table0_1::create_sql(&client).unwrap(), "CREATE TABLE database1.table0_1 AS database1.table0_2");
+    assert_eq!(Database1Table0_2::create_sql(&client).unwrap(), Database1Table0_2::CREATE_SQL);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_storage_table() {
+    assert_eq!(Database1Table0_1::storage_table().as_deref(), Some("database1.table0_2"));
+    assert_eq!(Database1Table0_6::storage_table().as_deref(), Some("database1.table0_5"));
+    assert_eq!(Database1Sub_Db0Table0_3::storage_table().as_deref(), Some("database1.`sub_db0.table0_3`"));
+    assert_eq!(Database1Table0_5::object_kind(), "TABLE");
+}
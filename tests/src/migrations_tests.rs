@@ -0,0 +1,78 @@
+use db_interfaces::clickhouse::{
+    errors::ClickhouseError,
+    migrations::{on_cluster, pending_migrations, split_statements, AppliedMigration, Migration}
+};
+
+fn applied(migration: &Migration) -> AppliedMigration {
+    AppliedMigration { version: migration.version, name: migration.name.clone(), checksum: migration.checksum.clone() }
+}
+
+#[test]
+fn test_split_statements() {
+    let sql = "-- adds the column\nALTER TABLE db.table0 ADD COLUMN IF NOT EXISTS note String DEFAULT ';';\n\n/* ; */ ALTER TABLE db.table0 COMMENT \
+               COLUMN note 'a \\' ;';\n-- trailing comment\n";
+
+    assert_eq!(
+        split_statements(sql),
+        ["ALTER TABLE db.table0 ADD COLUMN IF NOT EXISTS note String DEFAULT ';'", "/* ; */ ALTER TABLE db.table0 COMMENT COLUMN note 'a \\' ;'"]
+    );
+    assert!(split_statements("-- nothing\n;\n").is_empty());
+}
+
+#[test]
+fn test_on_cluster() {
+    assert_eq!(
+        on_cluster("ALTER TABLE db.table0 ADD COLUMN note String", Some("cluster0")),
+        "ALTER TABLE db.table0 ON CLUSTER cluster0 ADD COLUMN note String"
+    );
+    assert_eq!(
+        on_cluster("create table if not exists db.table1 (id UInt64) ENGINE = MergeTree ORDER BY id", Some("cluster0")),
+        "create table if not exists db.table1 ON CLUSTER cluster0 (id UInt64) ENGINE = MergeTree ORDER BY id"
+    );
+    assert_eq!(on_cluster("OPTIMIZE TABLE db.table0 FINAL", Some("cluster0")), "OPTIMIZE TABLE db.table0 ON CLUSTER cluster0 FINAL");
+    assert_eq!(on_cluster("DROP TABLE db.table0 ON CLUSTER other", Some("cluster0")), "DROP TABLE db.table0 ON CLUSTER other");
+    assert_eq!(
+        on_cluster("ALTER TABLE db.table0 MODIFY COMMENT 'runs on cluster x'", Some("cluster0")),
+        "ALTER TABLE db.table0 ON CLUSTER cluster0 MODIFY COMMENT 'runs on cluster x'"
+    );
+    assert_eq!(on_cluster("INSERT INTO db.table0 VALUES (1)", Some("cluster0")), "INSERT INTO db.table0 VALUES (1)");
+    assert_eq!(on_cluster("ALTER TABLE db.table0 DROP COLUMN note", None), "ALTER TABLE db.table0 DROP COLUMN note");
+}
+
+#[test]
+fn test_read_migrations_dir() {
+    let dir = std::env::temp_dir().join(format!("db_interfaces_migrations_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("0002_add_note.sql"), "ALTER TABLE db.table0 ADD COLUMN note String").unwrap();
+    std::fs::write(dir.join("0001_create_table0.sql"), "CREATE TABLE db.table0 (id UInt64) ENGINE = MergeTree ORDER BY id").unwrap();
+    std::fs::write(dir.join("README.md"), "not a migration").unwrap();
+
+    let migrations = Migration::read_dir(&dir).unwrap();
+    assert_eq!(
+        migrations
+            .iter()
+            .map(|migration| (migration.version, migration.name.as_str()))
+            .collect::<Vec<_>>(),
+        [(1, "create_table0"), (2, "add_note")]
+    );
+
+    std::fs::write(dir.join("2_duplicate.sql"), "SELECT 1").unwrap();
+    assert!(matches!(Migration::read_dir(&dir), Err(ClickhouseError::InvalidMigration { .. })));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_pending_migrations() {
+    let migrations = vec![Migration::new(1, "create_table0", "CREATE TABLE ..."), Migration::new(2, "add_note", "ALTER TABLE ...")];
+
+    let pending = pending_migrations(migrations.clone(), vec![applied(&migrations[0])]).unwrap();
+    assert_eq!(pending, migrations[1..]);
+
+    let mut changed = applied(&migrations[0]);
+    changed.checksum = Migration::new(1, "create_table0", "CREATE TABLE IF NOT EXISTS ...").checksum;
+    assert!(matches!(pending_migrations(migrations.clone(), vec![changed]), Err(ClickhouseError::MigrationChanged { version: 1, .. })));
+
+    let removed = AppliedMigration { version: 3, name: "removed".to_string(), checksum: String::new() };
+    assert!(matches!(pending_migrations(migrations, vec![removed]), Err(ClickhouseError::MigrationMissing { version: 3, .. })));
+}
@@ -0,0 +1,102 @@
+use clickhouse::query::Query;
+use db_interfaces::{
+    database_table,
+    params::BindParameters,
+    test_utils::mock::{client::MockDatabase, types::QueryMatcher},
+    Database
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MockType0 {
+    id:   u64,
+    name: String
+}
+
+database_table!(MockTable0, MockType0);
+
+fn rows() -> Vec<MockType0> {
+    (0..3)
+        .map(|i| MockType0 { id: i, name: format!("name{i}") })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_mock_records_inserts_and_queries() {
+    let mock = MockDatabase::<()>::new();
+
+    let rows = rows();
+    mock.insert_many::<MockTable0>(&rows[..2]).await.unwrap();
+    mock.insert_one::<MockTable0>(&rows[2]).await.unwrap();
+    assert_eq!(mock.inserted::<MockTable0>(), rows);
+
+    mock.execute_remote("TRUNCATE TABLE db.table0", &())
+        .await
+        .unwrap();
+    assert_eq!(mock.executed()[0].sql, "TRUNCATE TABLE db.table0");
+
+    mock.on_query("SELECT * FROM db.table0", &rows).unwrap();
+    mock.on_query(QueryMatcher::regex(r"FROM db\.table0 WHERE id = \?").unwrap(), &rows[1..2])
+        .unwrap();
+
+    let queried: Vec<MockType0> = mock
+        .query_many("SELECT * FROM db.table0", &())
+        .await
+        .unwrap();
+    assert_eq!(queried, rows);
+
+    let queried: MockType0 = mock
+        .query_one("SELECT * FROM db.table0 WHERE id = ?", &1u64)
+        .await
+        .unwrap();
+    assert_eq!(queried, rows[1]);
+
+    let recorded = mock.queries();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(recorded[1].params, [1u64]);
+
+    // queries without a registered response fail instead of returning no rows
+    let queried = mock
+        .query_one_optional::<MockType0, _>("SELECT * FROM db.table1", &())
+        .await;
+    assert!(queried.is_err());
+}
+
+#[tokio::test]
+async fn test_mock_raw_responses() {
+    let mock = MockDatabase::<()>::new();
+
+    mock.on_query_raw("SELECT count() FROM db.table0", "[{\"id\":0,\"name\":\"name0\"}]");
+
+    let raw = mock
+        .query_raw::<MockType0, _>("SELECT count() FROM db.table0", &())
+        .await
+        .unwrap();
+    assert_eq!(raw, b"[{\"id\":0,\"name\":\"name0\"}]");
+
+    let queried: Vec<MockType0> = mock
+        .query_many("SELECT count() FROM db.table0", &())
+        .await
+        .unwrap();
+    assert_eq!(queried, rows()[..1]);
+}
+
+/// params that only implement the clickhouse binding
+struct ClickhouseParams;
+
+impl BindParameters for ClickhouseParams {
+    fn bind_query(&self, query: Query) -> Query {
+        query.bind(1u64)
+    }
+}
+
+#[tokio::test]
+async fn test_mock_clickhouse_only_params() {
+    let mock = MockDatabase::<()>::new();
+
+    let executed = mock
+        .execute_remote("DELETE FROM db.table0 WHERE id = ?", &ClickhouseParams)
+        .await;
+    assert!(executed.is_err());
+    assert!(mock.executed().is_empty());
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use db_interfaces::{
+    clickhouse::cancel::attempt_query_id,
+    database_table,
+    options::{CancellationToken, QueryOptions},
+    test_utils::mock::client::MockDatabase,
+    Database
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OptionsType0 {
+    id: u64
+}
+
+database_table!(OptionsTable0, OptionsType0);
+
+#[test]
+fn test_attempt_query_id() {
+    assert_eq!(attempt_query_id(Some("query0"), 0), "query0");
+    // the retries and failovers don't reuse the id of the attempt being killed
+    assert_eq!(attempt_query_id(Some("query0"), 1), "query0-1");
+    assert_eq!(attempt_query_id(Some("query0"), 2), "query0-2");
+    assert_ne!(attempt_query_id(None, 1), attempt_query_id(None, 1));
+}
+
+#[test]
+fn test_query_options_server_params() {
+    assert!(QueryOptions::new().is_empty());
+
+    let options = QueryOptions::new()
+        .with_max_execution_time(Duration::from_millis(1500))
+        .with_max_memory_usage(1 << 30)
+        .with_final(true)
+        .with_setting("max_threads", 4)
+        .with_setting("max_threads", 8)
+        .with_query_id("query0")
+        .with_log_comment("daily report")
+        .with_timeout(Duration::from_secs(30));
+
+    assert!(!options.is_empty());
+    assert_eq!(
+        options.server_params().collect::<Vec<_>>(),
+        [
+            ("final", "1"),
+            ("max_execution_time", "2"),
+            ("max_memory_usage", "1073741824"),
+            ("max_threads", "8"),
+            ("query_id", "query0"),
+            ("log_comment", "daily report")
+        ]
+    );
+
+    let options = QueryOptions::new().with_timeout(Duration::from_secs(1));
+    assert!(!options.is_empty());
+    assert_eq!(options.server_params().count(), 0);
+
+    let options = QueryOptions::new().with_cancellation(CancellationToken::new());
+    assert!(!options.is_empty());
+    assert_eq!(options.server_params().count(), 0);
+    // the table only labels the metrics, it isn't sent to the server
+    let options = QueryOptions::new().with_table::<OptionsTable0>();
+    assert_eq!(options.table, Some("OptionsTable0"));
+    assert!(!options.is_empty());
+    assert_eq!(options.server_params().count(), 0);
+}
+
+#[tokio::test]
+async fn test_query_options_ignored_by_default() {
+    let mock = MockDatabase::<()>::new();
+    let options = QueryOptions::new().with_final(true);
+
+    mock.insert_one_with_options::<OptionsTable0>(&OptionsType0 { id: 1 }, &options)
+        .await
+        .unwrap();
+    assert_eq!(mock.inserted::<OptionsTable0>(), [OptionsType0 { id: 1 }]);
+
+    mock.on_query("SELECT id FROM db.table0", &[OptionsType0 { id: 1 }])
+        .unwrap();
+    let queried: OptionsType0 = mock
+        .query_one_with_options("SELECT id FROM db.table0", &(), &options)
+        .await
+        .unwrap();
+    assert_eq!(queried.id, 1);
+
+    mock.execute_remote_with_options("OPTIMIZE TABLE db.table0", &(), &options)
+        .await
+        .unwrap();
+    assert_eq!(mock.executed()[0].sql, "OPTIMIZE TABLE db.table0");
+}
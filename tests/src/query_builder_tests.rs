@@ -0,0 +1,102 @@
+use db_interfaces::{
+    clickhouse::{
+        errors::ClickhouseError,
+        query_builder::{quote_identifier, quote_string, Order, SelectQuery, SettingValue}
+    },
+    params::BindParameters,
+    test_utils::mock::client::MockDatabase,
+    Database
+};
+
+use crate::{
+    handle_tests::{Balances, Dbms3},
+    macro_tests::{Database1Sub_Db0Table0_3, Dbms0}
+};
+
+#[test]
+fn test_quote_identifier() {
+    assert_eq!(quote_identifier("type0"), "type0");
+    assert_eq!(quote_identifier("_type0"), "_type0");
+    assert_eq!(quote_identifier("sub_db0.table0_3"), "`sub_db0.table0_3`");
+    assert_eq!(quote_identifier("0type"), "`0type`");
+    assert_eq!(quote_identifier("a` FROM x; --"), "`a\\` FROM x; --`");
+    assert_eq!(quote_string("it's \\"), "'it\\'s \\\\'");
+}
+
+#[test]
+fn test_select_query() {
+    let (sql, params) = SelectQuery::from_table::<Database1Sub_Db0Table0_3, Dbms0>()
+        .columns(["type0", "type 1"])
+        .expr("count() AS count")
+        .with_final()
+        .sample(0.1)
+        .filter("type2 > ?", 1.5f64)
+        .filter_eq("type0", "pair0".to_string())
+        .filter("type1 BETWEEN ? AND ?", (10u64, 20u64))
+        .group_by("type0")
+        .group_by("type 1")
+        .order_by("type0", Order::Desc)
+        .limit(100)
+        .offset(10)
+        .setting("max_threads", 4u64)
+        .setting("log_comment", "it's")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        sql,
+        "SELECT type0, `type 1`, count() AS count FROM database1.`sub_db0.table0_3` FINAL SAMPLE 0.1 WHERE (type2 > ?) AND (type0 = ?) AND (type1 \
+         BETWEEN ? AND ?) GROUP BY type0, `type 1` ORDER BY type0 DESC LIMIT 100 OFFSET 10 SETTINGS max_threads = 4, log_comment = 'it\\'s'"
+    );
+    assert_eq!(params.param_values().unwrap(), [serde_json::json!(1.5), "pair0".into(), 10u64.into(), 20u64.into()]);
+
+    assert_eq!(SelectQuery::from("database0", "table0_0").sql().unwrap(), "SELECT * FROM database0.table0_0");
+    assert_eq!(
+        SelectQuery::from("database0", "table0_0")
+            .setting("max_threads", 8)
+            .sql()
+            .unwrap(),
+        "SELECT * FROM database0.table0_0 SETTINGS max_threads = 8"
+    );
+    assert_eq!(SettingValue::from(-1), SettingValue::Int(-1));
+}
+
+#[test]
+fn test_select_query_invalid_sample() {
+    for ratio in [f64::NAN, 0.0, -0.5, 1.5] {
+        let built = SelectQuery::from("database0", "table0_0")
+            .sample(ratio)
+            .build();
+        assert!(matches!(built, Err(ClickhouseError::InvalidQuery(reason)) if reason.contains("isn't in (0, 1]")));
+    }
+}
+
+#[test]
+fn test_select_query_invalid_setting() {
+    let built = SelectQuery::from("database0", "table0_0")
+        .setting("max_threads", f64::INFINITY)
+        .setting("priority", f64::NAN)
+        .build();
+
+    // the first invalid part is returned
+    assert!(matches!(built, Err(ClickhouseError::InvalidQuery(reason)) if reason == "setting max_threads = inf isn't a finite number"));
+}
+
+#[tokio::test]
+async fn test_select_query_binds_params() {
+    let mock = MockDatabase::<Dbms3>::new();
+    let rows = vec![Balances::default()];
+    mock.on_query("SELECT account, amount FROM database4.balances WHERE (account = ?) LIMIT 1", &rows)
+        .unwrap();
+
+    let (sql, params) = SelectQuery::from_table::<Balances, Dbms3>()
+        .columns(["account", "amount"])
+        .filter_eq("account", "account0")
+        .limit(1)
+        .build()
+        .unwrap();
+    let queried: Vec<Balances> = mock.query_many(sql, &params).await.unwrap();
+
+    assert_eq!(queried, rows);
+    assert_eq!(mock.queries()[0].params, ["account0"]);
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use db_interfaces::{
+    clickhouse::{errors::ClickhouseError, retry::RetryPolicy},
+    errors::DatabaseError
+};
+
+fn send_error() -> DatabaseError {
+    ClickhouseError::SharedSendError("closed".to_string()).into()
+}
+
+#[test]
+fn test_retry_backoff() {
+    let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(300)).with_jitter(0.0);
+
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    assert_eq!(policy.backoff(100), Duration::from_millis(300));
+
+    let policy = policy.with_jitter(0.5);
+    for attempt in 1..10 {
+        let backoff = policy.backoff(attempt);
+        assert!(backoff >= Duration::from_millis(50) && backoff <= Duration::from_millis(300));
+    }
+}
+
+#[test]
+fn test_retry_backoff_invalid_values() {
+    let mut policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(300)).with_jitter(f64::NAN);
+
+    policy.multiplier = f64::NAN;
+    assert_eq!(policy.backoff(2), Duration::from_millis(300));
+
+    policy.multiplier = -2.0;
+    assert_eq!(policy.backoff(2), Duration::from_millis(300));
+
+    policy.multiplier = 1e300;
+    policy.max_backoff = Duration::MAX;
+    assert_eq!(policy.backoff(u32::MAX), Duration::MAX);
+}
+
+#[tokio::test]
+async fn test_retry_run() {
+    let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(1))
+        .with_classifier(|e| matches!(e, DatabaseError::ClickhouseError(ClickhouseError::SharedSendError(_))));
+
+    // retryable errors are retried until the attempts run out
+    let mut attempts = 0;
+    let res = policy
+        .run(|| {
+            attempts += 1;
+            async { Err::<(), _>(send_error()) }
+        })
+        .await;
+    assert!(res.is_err());
+    assert_eq!(attempts, 3);
+
+    // succeeds once the error goes away
+    let mut attempts = 0;
+    let res = policy
+        .run(|| {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 2 {
+                    Err(send_error())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+    assert_eq!(res.unwrap(), 2);
+
+    // other errors aren't retried
+    let mut attempts = 0;
+    let res = policy
+        .run(|| {
+            attempts += 1;
+            async { Err::<(), _>(ClickhouseError::SqlFileReadError("missing".to_string()).into()) }
+        })
+        .await;
+    assert!(res.is_err());
+    assert_eq!(attempts, 1);
+}
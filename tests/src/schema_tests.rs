@@ -0,0 +1,91 @@
+use db_interfaces::clickhouse::schema::{
+    column_mismatch, parse_columns, row_columns, ColumnMismatch, ConstMessage, LiveColumn, SchemaDiff, TypeMismatch
+};
+
+use crate::macro_tests::Type0;
+
+fn columns(columns: &[(&str, &str)]) -> Vec<(String, String)> {
+    columns
+        .iter()
+        .map(|(name, column_type)| (name.to_string(), column_type.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_parse_columns() {
+    let create_sql = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/sql/tables/sub_db0.table0_3.sql")).unwrap();
+    assert_eq!(parse_columns(&create_sql), columns(&[("type0", "String"), ("type1", "UInt64"), ("type2", "Float64")]));
+
+    let create_sql = "CREATE TABLE IF NOT EXISTS db.table0 ON CLUSTER cluster0 (
+        id UInt64 CODEC(Delta, ZSTD),
+        `amount` Decimal(38, 18) DEFAULT 0,
+        tags Map(String, Array(Nullable(String))) COMMENT 'a, b',
+        note LowCardinality(String) MATERIALIZED concat('(', toString(id)),
+        INDEX idx_id id TYPE minmax GRANULARITY 1
+    ) ENGINE = MergeTree(id) ORDER BY id";
+    assert_eq!(
+        parse_columns(create_sql),
+        columns(&[
+            ("id", "UInt64"),
+            ("amount", "Decimal(38, 18)"),
+            ("tags", "Map(String, Array(Nullable(String)))"),
+            ("note", "LowCardinality(String)")
+        ])
+    );
+
+    assert!(parse_columns("CREATE TABLE db.table1 AS db.table0 ENGINE = Distributed('cluster0', 'db', 'table0')").is_empty());
+    assert!(parse_columns("CREATE MATERIALIZED VIEW db.view0 TO db.table0 AS SELECT sum(id) FROM db.table1").is_empty());
+}
+
+#[test]
+fn test_schema_diff() {
+    let live = vec![
+        LiveColumn::new("type1", "UInt64"),
+        LiveColumn::new("type0", "String"),
+        LiveColumn::new("type3", "Float32"),
+        LiveColumn::new("computed", "String").with_default_kind("MATERIALIZED"),
+    ];
+    let sql_columns = columns(&[("type0", "LowCardinality(String)"), ("type1", "UInt64")]);
+
+    let diff = SchemaDiff::new("database1.table0_1", &["type0", "type1", "type2"], &sql_columns, &live);
+    assert_eq!(diff.missing, ["type2"]);
+    assert_eq!(diff.extra, ["type3"]);
+    assert_eq!(diff.reordered, ["type0", "type1"]);
+    assert_eq!(
+        diff.type_mismatches,
+        [TypeMismatch { column: "type0".to_string(), expected: "LowCardinality(String)".to_string(), actual: "String".to_string() }]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "database1.table0_1: schema drifted, missing columns: type2, extra columns: type3, reordered columns: type0, type1, `type0` is String \
+         instead of LowCardinality(String)"
+    );
+
+    let matching = SchemaDiff::new("database1.table0_1", &["type1", "type0", "type3"], &columns(&[("type3", "Float32")]), &live);
+    assert!(matching.is_empty());
+}
+
+#[test]
+fn test_column_mismatch() {
+    const TYPE0: ColumnMismatch = column_mismatch(row_columns::<Type0>(), &["type0", "type1", "type2"]);
+    assert_eq!(TYPE0, ColumnMismatch::None);
+
+    assert_eq!(column_mismatch(&["type0", "type1"], &["type0", "type1", "type2"]), ColumnMismatch::Missing(2));
+    assert_eq!(column_mismatch(&["type0", "type1", "type2"], &["type0", "type1"]), ColumnMismatch::Extra);
+    assert_eq!(column_mismatch(&["type1", "type0"], &["type0", "type1"]), ColumnMismatch::Reordered(0));
+    assert_eq!(column_mismatch(row_columns::<String>(), &["type0"]), ColumnMismatch::None);
+
+    const MESSAGE: ConstMessage = ConstMessage::new()
+        .push("field `")
+        .push("type1")
+        .push("` is at position ")
+        .push_usize(10);
+    assert_eq!(MESSAGE.as_str(), "field `type1` is at position 10");
+    assert_eq!(
+        ConstMessage::new()
+            .push_usize(0)
+            .push(&"x".repeat(2000))
+            .as_str(),
+        "0"
+    );
+}
@@ -0,0 +1,94 @@
+use db_interfaces::{errors::DatabaseError, inserts::InsertChunkSize, sqlite::client::SqliteClient, sqlite_dbms, sqlite_table, Database};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SqliteType0 {
+    id:    u64,
+    name:  String,
+    value: f64
+}
+
+sqlite_dbms!(SqliteDbms0, [SqliteTable0]);
+
+sqlite_table!(
+    SqliteDbms0,
+    SqliteTable0,
+    SqliteType0,
+    "table0",
+    "CREATE TABLE IF NOT EXISTS table0 (id INTEGER NOT NULL, name TEXT NOT NULL, value REAL NOT NULL)"
+);
+
+fn rows() -> Vec<SqliteType0> {
+    (0..10)
+        .map(|i| SqliteType0 { id: i, name: format!("name{i}"), value: i as f64 / 2.0 })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sqlite_insert_and_query() {
+    let client = SqliteClient::<SqliteDbms0>::open_in_memory().unwrap();
+    client.create_tables().await.unwrap();
+
+    let rows = rows();
+    client.insert_many::<SqliteTable0>(&rows).await.unwrap();
+
+    let queried: Vec<SqliteType0> = client
+        .query_many("SELECT id, name, value FROM table0 ORDER BY id", &())
+        .await
+        .unwrap();
+    assert_eq!(queried, rows);
+
+    let queried: SqliteType0 = client
+        .query_one("SELECT id, name, value FROM table0 WHERE name = ?", &"name3")
+        .await
+        .unwrap();
+    assert_eq!(queried, rows[3]);
+
+    let queried: Option<SqliteType0> = client
+        .query_one_optional("SELECT id, name, value FROM table0 WHERE id > ?", &100u64)
+        .await
+        .unwrap();
+    assert_eq!(queried, None);
+}
+
+#[tokio::test]
+async fn test_sqlite_query_stream_while_inserting() {
+    let client = SqliteClient::<SqliteDbms0>::open_in_memory().unwrap();
+    client.create_tables().await.unwrap();
+
+    // the client is used while the stream is consumed, with more rows than
+    // the stream would buffer
+    let rows = (0..2000)
+        .map(|i| SqliteType0 { id: i, name: format!("name{i}"), value: i as f64 / 2.0 })
+        .collect::<Vec<_>>();
+    client.insert_many::<SqliteTable0>(&rows).await.unwrap();
+
+    let mut stream = std::pin::pin!(client.query_stream::<SqliteType0, _>("SELECT id, name, value FROM table0 ORDER BY id", &()));
+    let mut streamed = 0;
+    while let Some(row) = stream.try_next().await.unwrap() {
+        let copy = SqliteType0 { id: row.id + 2000, ..row };
+        client.insert_one::<SqliteTable0>(&copy).await.unwrap();
+        streamed += 1;
+    }
+
+    assert_eq!(streamed, 2000);
+    assert_eq!(
+        client
+            .query_one::<u64, _>("SELECT count(*) FROM table0", &())
+            .await
+            .unwrap(),
+        4000
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_insert_iter_error() {
+    let client = SqliteClient::<SqliteDbms0>::open_in_memory().unwrap();
+
+    // nothing was committed, the error isn't a partial insert
+    let inserted = client
+        .insert_iter::<SqliteTable0, _>(rows(), InsertChunkSize::Rows(4))
+        .await;
+    assert!(matches!(inserted, Err(DatabaseError::SqliteError(_))));
+}